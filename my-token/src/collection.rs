@@ -0,0 +1,322 @@
+//! Collection NFTs: a parent NFT naming a collection and the child NFTs minted
+//! under it.
+//!
+//! The parent is minted like any NFT, from the UTXO whose hash is its identity.
+//! Children can only be minted by a transaction that also spends the parent and
+//! recreates it with `minted_items` advanced by the number of children, so the
+//! parent holder controls the collection and `max_items` bounds its size.
+
+use std::collections::BTreeMap;
+
+use charms_sdk::data::{App, Data, Transaction, UtxoId, B32, NFT};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{hash, ContractError, ContractInput, NftContent, MAX_NAME_LEN};
+
+/// Content of an NFT of this contract.
+///
+/// The variants are told apart by their fields: a parent holds `collection_name`
+/// and a child `parent`, with no fields of any other variant. Everything else,
+/// including NFTs minted before collections existed, is a token reserve, which
+/// holds neither field. Content mixing the fields of several variants is not
+/// valid, so every app reads an NFT as the same variant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum NftState {
+    /// Parent NFT of a collection
+    Collection(CollectionContent),
+    /// NFT minted under a collection
+    Child(ChildContent),
+    /// Reserve NFT controlling the supply of a fungible token
+    Reserve(Box<NftContent>),
+}
+
+// Deserialized through `Data` rather than `#[serde(untagged)]`: untagged enums
+// buffer their input in a human-readable form, which breaks decoding `B32`
+// from CBOR.
+impl<'de> Deserialize<'de> for NftState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = Data::deserialize(deserializer)?;
+        data.value()
+            .map(Self::Collection)
+            .or_else(|_| data.value().map(Self::Child))
+            .ok()
+            .or_else(|| {
+                let content = data
+                    .value()
+                    .ok()
+                    .filter(|_| !has_collection_fields(&data))?;
+                Some(Self::Reserve(Box::new(content)))
+            })
+            .ok_or_else(|| D::Error::custom("data is not valid NFT content"))
+    }
+}
+
+/// Fields marking NFT content as a collection parent or child, read whatever
+/// else the content holds.
+#[derive(Deserialize)]
+struct CollectionFields {
+    #[serde(default)]
+    collection_name: Option<Data>,
+    #[serde(default)]
+    parent: Option<Data>,
+}
+
+/// Returns whether `data` holds a field of a collection parent or child.
+fn has_collection_fields(data: &Data) -> bool {
+    data.value::<CollectionFields>()
+        .is_ok_and(|fields| fields.collection_name.is_some() || fields.parent.is_some())
+}
+
+/// Represents the content of a parent collection NFT.
+///
+/// The name and `max_items` are fixed at mint; only `minted_items` changes, as
+/// children are minted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollectionContent {
+    /// Human readable collection name
+    pub collection_name: String,
+    /// Maximum number of children the collection can ever have
+    pub max_items: u32,
+    /// Number of children minted so far
+    #[serde(default)]
+    pub minted_items: u32,
+}
+
+/// Represents the content of an NFT minted under a collection.
+///
+/// Its identity is the [`child_identity`] of `parent` and `number`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChildContent {
+    /// Identity of the parent collection NFT
+    pub parent: B32,
+    /// Zero-based mint order of the child in the collection
+    pub number: u32,
+    /// Human readable item name
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Derives the identity of the child NFT `number` of the collection `parent`.
+///
+/// The identity is the SHA-256 hash of the parent identity followed by the
+/// big-endian `number`, so every child of a collection is a distinct NFT.
+pub fn child_identity(parent: &B32, number: u32) -> B32 {
+    let hash = Sha256::new()
+        .chain_update(parent.0)
+        .chain_update(number.to_be_bytes())
+        .finalize();
+    B32(hash.into())
+}
+
+/// Returns whether the NFT of `nft_app` in the transaction is a collection parent or child.
+pub fn is_collection_nft(nft_app: &App, tx: &Transaction) -> bool {
+    input_state(nft_app, tx)
+        .into_iter()
+        .chain(output_states(nft_app, tx))
+        .any(|state| !matches!(state, NftState::Reserve(_)))
+}
+
+/// Validates a transaction for a collection parent or child NFT.
+///
+/// Without the NFT in the inputs this is a mint of a parent or a child. A spent
+/// parent may mint children, and a spent child must be preserved. A declared
+/// `op` restricts the transaction to minting or to moving the NFT unchanged.
+pub fn collection_nft_satisfied(
+    nft_app: &App,
+    tx: &Transaction,
+    w: &Data,
+    op: Option<ContractInput>,
+) -> Result<(), ContractError> {
+    match (op, input_state(nft_app, tx)) {
+        (
+            Some(
+                ContractInput::Burn
+                | ContractInput::UpdateMetadata
+                | ContractInput::Claim
+                | ContractInput::Swap
+                | ContractInput::Fill
+                | ContractInput::Refund { .. }
+                | ContractInput::Release { .. }
+                | ContractInput::Stake { .. }
+                | ContractInput::Unstake { .. },
+            ),
+            _,
+        ) => Err(ContractError::UnsupportedOperation),
+        (Some(ContractInput::Transfer), None) => Err(ContractError::MissingInputNft),
+        (Some(ContractInput::Transfer), Some(_)) | (None, Some(NftState::Child(_))) => {
+            can_preserve_state(nft_app, tx)
+        },
+        (_, None) => can_mint_collection_nft(nft_app, tx, w),
+        (_, Some(NftState::Collection(content))) => can_mint_children(nft_app, tx, &content),
+        (_, Some(_)) => Err(ContractError::NftStateChanged),
+    }
+}
+
+/// Validates the mint of a parent or child NFT.
+///
+/// A parent must be minted from the witness UTXO like a reserve NFT, with a
+/// name, room for children and none minted yet. A child must be minted under
+/// its spent parent (see [`can_mint_child`]).
+fn can_mint_collection_nft(nft_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let states = output_states(nft_app, tx);
+    // can mint exactly one NFT.
+    let [state] = states.as_slice() else {
+        return Err(ContractError::NftCountMismatch(states.len()));
+    };
+    let content = match state {
+        NftState::Collection(content) => content,
+        NftState::Child(content) => return can_mint_child(nft_app, tx, content),
+        NftState::Reserve(_) => return Err(ContractError::InvalidNftContent),
+    };
+
+    // can only mint the parent if the hash of `w` is its identity and that UTXO is spent.
+    let w_str: String = w.value().map_err(|_| ContractError::WitnessNotString)?;
+    if hash(&w_str) != nft_app.identity {
+        return Err(ContractError::IdentityMismatch);
+    }
+    let w_utxo_id =
+        UtxoId::from_str(&w_str).map_err(|_| ContractError::InvalidWitnessUtxoId(w_str.clone()))?;
+    if !tx.ins.iter().any(|(utxo_id, _)| utxo_id == &w_utxo_id) {
+        return Err(ContractError::WitnessUtxoNotSpent);
+    }
+
+    if content.collection_name.len() > MAX_NAME_LEN {
+        return Err(ContractError::NameTooLong(content.collection_name.len()));
+    }
+    if content.collection_name.is_empty() || content.max_items == 0 || content.minted_items != 0 {
+        return Err(ContractError::InvalidCollection);
+    }
+    Ok(())
+}
+
+/// Validates the mint of a child NFT.
+///
+/// The child identity must derive from its parent and number, and the parent
+/// must be spent and recreated with `minted_items` advanced past `number`. The
+/// parent's own contract checks the remaining accounting.
+fn can_mint_child(
+    nft_app: &App,
+    tx: &Transaction,
+    content: &ChildContent,
+) -> Result<(), ContractError> {
+    if child_identity(&content.parent, content.number) != nft_app.identity {
+        return Err(ContractError::IdentityMismatch);
+    }
+    if let Some(name) = &content.name {
+        if name.len() > MAX_NAME_LEN {
+            return Err(ContractError::NameTooLong(name.len()));
+        }
+    }
+
+    let parent_app = App {
+        tag: NFT,
+        identity: content.parent.clone(),
+        vk: nft_app.vk.clone(),
+    };
+    let parent_outputs = output_states(&parent_app, tx);
+    let (Some(NftState::Collection(parent_in)), [NftState::Collection(parent_out)]) =
+        (input_state(&parent_app, tx), parent_outputs.as_slice())
+    else {
+        return Err(ContractError::MissingParentNft);
+    };
+    if !(parent_in.minted_items..parent_out.minted_items).contains(&content.number) {
+        return Err(ContractError::InvalidCollectionIndex {
+            index: content.number,
+            size: parent_out.minted_items,
+        });
+    }
+    Ok(())
+}
+
+/// Validates the transition of a spent parent NFT.
+///
+/// The parent must be recreated with only `minted_items` advanced, at most to
+/// `max_items`, and the transaction must mint exactly one child for every
+/// number in between. Leaving `minted_items` unchanged transfers the parent.
+fn can_mint_children(
+    nft_app: &App,
+    tx: &Transaction,
+    input_content: &CollectionContent,
+) -> Result<(), ContractError> {
+    let states = output_states(nft_app, tx);
+    let output_content = match states.as_slice() {
+        [] => return Err(ContractError::MissingOutputNft),
+        [NftState::Collection(content)] => content,
+        _ => return Err(ContractError::NftStateChanged),
+    };
+    if output_content.minted_items > output_content.max_items {
+        return Err(ContractError::CollectionFull {
+            max_items: output_content.max_items,
+        });
+    }
+    let expected_content = CollectionContent {
+        minted_items: output_content.minted_items,
+        ..input_content.clone()
+    };
+    if *output_content != expected_content
+        || output_content.minted_items < input_content.minted_items
+    {
+        return Err(ContractError::NftStateChanged);
+    }
+
+    // count the children newly minted under this parent; children moved
+    // alongside it are already accounted for.
+    let mut children: BTreeMap<u32, usize> = BTreeMap::new();
+    for (app, data) in tx.outs.iter().flatten() {
+        let spent = tx.ins.iter().any(|(_, charms)| charms.contains_key(app));
+        if app.tag != NFT || app.vk != nft_app.vk || spent {
+            continue;
+        }
+        if let Ok(NftState::Child(child)) = data.value::<NftState>() {
+            if child.parent == nft_app.identity {
+                *children.entry(child.number).or_default() += 1;
+            }
+        }
+    }
+    let minted = input_content.minted_items..output_content.minted_items;
+    if let Some(&number) = children.keys().find(|number| !minted.contains(number)) {
+        return Err(ContractError::InvalidCollectionIndex {
+            index: number,
+            size: output_content.minted_items,
+        });
+    }
+    for number in minted {
+        match children.get(&number) {
+            None => return Err(ContractError::MissingCollectionItem(number)),
+            Some(1) => {},
+            Some(_) => return Err(ContractError::DuplicateCollectionItem(number)),
+        }
+    }
+    Ok(())
+}
+
+/// Validates that a spent parent or child NFT is recreated unchanged.
+fn can_preserve_state(nft_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let input = input_state(nft_app, tx).ok_or(ContractError::MissingInputNft)?;
+    match output_states(nft_app, tx).as_slice() {
+        [] => Err(ContractError::MissingOutputNft),
+        [output] if *output == input => Ok(()),
+        _ => Err(ContractError::NftStateChanged),
+    }
+}
+
+/// Reads the first valid [`NftState`] of `nft_app` from the transaction inputs.
+fn input_state(nft_app: &App, tx: &Transaction) -> Option<NftState> {
+    tx.ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(nft_app))
+        .find_map(|data| data.value().ok())
+}
+
+/// Reads every valid [`NftState`] of `nft_app` from the transaction outputs.
+fn output_states(nft_app: &App, tx: &Transaction) -> Vec<NftState> {
+    tx.outs
+        .iter()
+        .filter_map(|charms| charms.get(nft_app))
+        .filter_map(|data| data.value().ok())
+        .collect()
+}
@@ -0,0 +1,119 @@
+//! Hash time-locked token outputs for cross-chain swaps.
+//!
+//! Tokens in the locked form carry an [`Htlc`]: the recipient can claim them by
+//! revealing the preimage of `payment_hash`, as in a Lightning payment, and the
+//! sender can take them back once the chain passes `timeout_height`, which the
+//! refund proves with a timelocked input (see [`HeightProof`](crate::HeightProof)).
+//! The locked output must sit at a script both parties can spend; the contract
+//! decides where the tokens go.
+
+use charms_sdk::data::{App, Data, Transaction};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{can_transfer_token, token_amount, ContractError, TokenAmount};
+
+/// Terms of a hash time-locked token output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Htlc {
+    /// Hex-encoded SHA-256 hash of the preimage that unlocks the tokens
+    pub payment_hash: String,
+    /// Destination script receiving the tokens when claimed with the preimage
+    pub recipient_dest: Vec<u8>,
+    /// Destination script receiving the tokens when refunded
+    pub refund_dest: Vec<u8>,
+    /// Block height after which the tokens can be refunded
+    pub timeout_height: u64,
+}
+
+/// Witness of an HTLC claim, supplied as the private input `w` of the token app.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HtlcWitness {
+    /// Hex-encoded preimage of the payment hash
+    pub preimage: String,
+}
+
+/// Returns whether the transaction spends `token_app` tokens in the locked form.
+pub fn has_htlc_input(token_app: &App, tx: &Transaction) -> Result<bool, ContractError> {
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if matches!(token_amount(data)?, TokenAmount::Locked { .. }) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Validates the claim or refund of the HTLC spent by the transaction.
+///
+/// Exactly one HTLC may be spent. Without a `refund_height` this is a claim: the
+/// [`HtlcWitness`] must reveal the preimage and the locked tokens go to
+/// `recipient_dest`. With one, proven by the public input, it must be past
+/// `timeout_height` and the tokens go back to `refund_dest`. Either way they are
+/// paid in the transferable form, which no other terms bind, and must balance as
+/// in a transfer.
+pub fn can_unlock_htlc(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+    refund_height: Option<u64>,
+) -> Result<(), ContractError> {
+    let mut htlcs = Vec::new();
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if let TokenAmount::Locked { locked, htlc } = token_amount(data)? {
+            htlcs.push((locked, htlc));
+        }
+    }
+    let [(locked, htlc)] = htlcs.as_slice() else {
+        return Err(ContractError::HtlcCountMismatch(htlcs.len()));
+    };
+
+    let dest = match refund_height {
+        None => {
+            let witness: HtlcWitness = w.value().map_err(|_| ContractError::InvalidPreimage)?;
+            let preimage =
+                hex::decode(&witness.preimage).map_err(|_| ContractError::InvalidPreimage)?;
+            if hex::encode(Sha256::digest(preimage)) != htlc.payment_hash.to_lowercase() {
+                return Err(ContractError::InvalidPreimage);
+            }
+            &htlc.recipient_dest
+        },
+        Some(height) if height > htlc.timeout_height => &htlc.refund_dest,
+        Some(_) => {
+            return Err(ContractError::HtlcNotExpired {
+                timeout_height: htlc.timeout_height,
+            })
+        },
+    };
+
+    // the locked tokens all go to the unlocked destination, free to spend.
+    let mut paid: u64 = 0;
+    for (output, charms) in tx.outs.iter().enumerate() {
+        let to_dest = tx
+            .coin_outs
+            .as_ref()
+            .and_then(|coin_outs| coin_outs.get(output))
+            .is_some_and(|destination| destination.dest == *dest);
+        if let Some(data) = charms.get(token_app).filter(|_| to_dest) {
+            if let TokenAmount::Transferable(amount) = token_amount(data)? {
+                paid = paid
+                    .checked_add(amount)
+                    .ok_or(ContractError::TokenAmountOverflow)?;
+            }
+        }
+    }
+    if paid < *locked {
+        return Err(ContractError::HtlcPayoutMismatch {
+            locked: *locked,
+            paid,
+        });
+    }
+    can_transfer_token(token_app, tx, w)
+}
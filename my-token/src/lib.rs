@@ -9,131 +9,1594 @@
 //! - **NFT Minting**: Create unique NFTs tied to specific UTXO identities
 //! - **Token Minting**: Mint fungible tokens controlled by corresponding NFT supply
 //! - **Supply Management**: Track and enforce token supply limits through NFT state
+//! - **Token Burning**: Destroy tokens, either retiring them or returning them to the reserve
+//! - **Token Metadata**: Optional decimals, name, image hash and URI fixed at NFT mint
+//! - **Metadata Updates**: The NFT holder can update the URI and description until
+//!   the metadata is locked
+//! - **Mint Authority**: Optionally gate token minting on a Schnorr signature or an
+//!   M-of-N set of them
+//! - **Finalization**: Permanently renounce minting to prove the supply is fixed
+//! - **Soulbound Tokens**: Optionally mint tokens that can be burned but never moved
+//! - **Pause Switch**: Optionally mint tokens that only move with the NFT spent,
+//!   so the NFT holder can pause their transfers
+//! - **Allowlist**: Optionally restrict token holders to destination scripts
+//!   committed in a Merkle root
+//! - **Airdrops**: Optionally let anyone claim allocations committed in a Merkle root
+//! - **Atomic Swaps**: Trade tokens for other tokens or bitcoin in a single spell
+//!   (see [`SwapOrder`])
+//! - **Limit Orders**: Sell tokens for sats through maker-signed orders that takers
+//!   fill partially (see [`LimitOrder`])
+//! - **HTLCs**: Lock tokens to a payment hash and timeout for cross-chain swaps
+//!   (see [`Htlc`])
+//! - **Vesting**: Release team and investor allocations linearly after a cliff
+//!   (see [`VestingSchedule`])
+//! - **Staking**: Lock tokens for rewards minted from the reserve at the NFT's
+//!   `staking_rate`
+//! - **NFT Burning**: Destroy the NFT once its reserve is exhausted
+//! - **Collections**: Mint a numbered collection of NFTs from a single UTXO, or
+//!   child NFTs under a parent collection NFT (see [`CollectionContent`])
+//! - **Mint Cap**: Optionally bound the amount minted by a single transaction
+//! - **Emission Schedule**: Optionally release the supply on a halving schedule,
+//!   against block heights proven by timelocked inputs (see [`HeightProof`])
+//! - **Explicit Operations**: Optionally declare the intent of a spell in its public input
+//! - **Typed Errors**: Every rejection is reported as a [`ContractError`] variant
 //!
 //! # Example
 //!
 //! ```ignore
-//! use my_token::{app_contract, NftContent};
+//! use my_token::{app_contract, validate, NftContent};
 //! use charms_sdk::data::{App, Transaction, Data};
 //!
 //! // The contract validates transactions through app_contract function
 //! let valid = app_contract(&app, &tx, &x, &w);
+//!
+//! // Or, to find out why a transaction is rejected
+//! if let Err(err) = validate(&app, &tx, &x, &w) {
+//!     eprintln!("{err}");
+//! }
 //! ```
 
-use charms_sdk::data::{
-    charm_values, check, sum_token_amount, App, Data, Transaction, UtxoId, B32, NFT, TOKEN,
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
 };
+
+use charms_sdk::data::{charm_values, App, Charms, Data, Transaction, UtxoId, B32, NFT, TOKEN};
+use k256::schnorr::{Signature, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+mod collection;
+mod htlc;
+mod merkle;
+mod orders;
+mod staking;
+mod swap;
+mod timelock;
+mod vesting;
+
+pub use collection::{child_identity, ChildContent, CollectionContent, NftState};
+pub use htlc::{Htlc, HtlcWitness};
+pub use merkle::{leaf_hash, merkle_proof, merkle_root, node_hash, verify_proof};
+pub use orders::{order_message, LimitOrder};
+pub use staking::STAKING_RATE_SCALE;
+pub use swap::SwapOrder;
+pub use timelock::{
+    p2wsh_script_pubkey, proven_height, timelock_script, HeightProof, LOCKTIME_THRESHOLD,
+};
+pub use vesting::VestingSchedule;
+
 /// Represents the content stored within an NFT.
 ///
 /// This structure tracks the token ticker and the remaining supply available
-/// for minting fungible tokens associated with this NFT.
+/// for minting fungible tokens associated with this NFT, along with the
+/// accounting for minted and burned tokens.
 ///
 /// # Fields
 ///
 /// * `ticker` - The token symbol/ticker string
 /// * `remaining` - The remaining supply of tokens that can be minted
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// * `burned` - Total amount of tokens permanently retired by burns
+/// * `recycle_burns` - Whether burned tokens go back to `remaining` instead of being retired
+/// * `max_supply` - The total supply the NFT was created with, if tracked
+/// * `minted` - Amount of tokens taken out of `remaining` so far
+/// * `decimals` - Number of decimal places wallets should display, at most [`MAX_DECIMALS`]
+/// * `name` - Human readable token name, at most [`MAX_NAME_LEN`] bytes
+/// * `image_hash` - SHA-256 hash of the token icon
+/// * `uri` - URI of the token description, at most [`MAX_URI_LEN`] bytes
+/// * `mint_authority` - x-only public key that must sign every token mint, if any
+/// * `mint_authorities` - x-only public keys of an M-of-N mint multisig, if any
+/// * `mint_threshold` - Number of `mint_authorities` that must sign every token mint
+/// * `finalized` - Whether minting has been permanently renounced
+/// * `max_mint_per_tx` - Maximum amount of tokens a single transaction may mint, if any
+/// * `emission` - Block-height based schedule bounding the cumulative `minted`, if any
+/// * `burnable` - Whether the NFT may be burned while tokens remain in `remaining`
+/// * `collection` - Position of the NFT in the collection it was minted with, if any
+/// * `description` - Human readable token description, at most [`MAX_DESCRIPTION_LEN`] bytes
+/// * `metadata_locked` - Whether `uri` and `description` are frozen
+/// * `transferable` - Whether minted tokens can be transferred, `true` unless soulbound
+/// * `pausable` - Whether minted tokens take the pausable form, set when the NFT is minted
+/// * `paused` - Whether token transfers are halted unless the NFT holder approves them
+/// * `allowlist_root` - Merkle root of the destination scripts allowed to hold tokens, if any
+/// * `airdrop` - Allocations anyone can claim against the reserve, if any
+///
+/// The metadata fields are validated when the NFT is minted. `uri` and `description`
+/// can be updated by the NFT holder until `metadata_locked` is set; the other
+/// metadata fields cannot change afterwards.
+///
+/// When `max_supply` is set the contract keeps `minted + remaining == max_supply`.
+/// NFTs without it (such as those minted with the original layout) keep `minted`
+/// unchanged and are not subject to the invariant.
+///
+/// Fields added after the original `ticker`/`remaining` layout default when absent,
+/// so NFTs minted before they existed still deserialize.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct NftContent {
     /// The token ticker symbol
     pub ticker: String,
     /// Remaining supply of tokens available for minting
     pub remaining: u64,
+    /// Total amount of tokens permanently retired by burns
+    #[serde(default)]
+    pub burned: u64,
+    /// Return burned tokens to `remaining` instead of retiring them
+    #[serde(default)]
+    pub recycle_burns: bool,
+    /// Total supply the NFT was created with (`minted + remaining`)
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    /// Amount of tokens minted out of the reserve, net of recycled burns
+    #[serde(default)]
+    pub minted: u64,
+    /// Number of decimal places used to display token amounts
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    /// Human readable token name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// SHA-256 hash of the token icon
+    #[serde(default)]
+    pub image_hash: Option<B32>,
+    /// URI of the token description
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// BIP-340 x-only public key whose signature is required to mint tokens
+    #[serde(default)]
+    pub mint_authority: Option<B32>,
+    /// BIP-340 x-only public keys of the mint multisig
+    #[serde(default)]
+    pub mint_authorities: Vec<B32>,
+    /// Number of distinct `mint_authorities` signatures required to mint tokens
+    #[serde(default)]
+    pub mint_threshold: u32,
+    /// Minting has been renounced; the NFT can never mint tokens again
+    #[serde(default)]
+    pub finalized: bool,
+    /// Maximum amount of tokens a single transaction may mint
+    #[serde(default)]
+    pub max_mint_per_tx: Option<u64>,
+    /// Emission schedule bounding the cumulative amount minted by block height
+    #[serde(default)]
+    pub emission: Option<EmissionSchedule>,
+    /// The NFT may be burned before `remaining` reaches zero
+    #[serde(default)]
+    pub burnable: bool,
+    /// Position of the NFT in its collection
+    #[serde(default)]
+    pub collection: Option<CollectionItem>,
+    /// Human readable token description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `uri` and `description` can no longer be updated
+    #[serde(default)]
+    pub metadata_locked: bool,
+    /// Minted tokens can be transferred; otherwise they are soulbound
+    #[serde(default = "transferable_by_default")]
+    pub transferable: bool,
+    /// Minted tokens take the pausable form, so every transfer spends the NFT
+    #[serde(default)]
+    pub pausable: bool,
+    /// Token transfers are halted unless the NFT is spent to approve them
+    #[serde(default)]
+    pub paused: bool,
+    /// Merkle root of the destination scripts allowed to receive tokens
+    #[serde(default)]
+    pub allowlist_root: Option<B32>,
+    /// Merkle airdrop of allocations claimable from the reserve
+    #[serde(default)]
+    pub airdrop: Option<Airdrop>,
+    /// Staking reward per staked token and block, in [`STAKING_RATE_SCALE`]ths of
+    /// a token. Rewards are minted like any other tokens, with the approval of
+    /// the NFT holder and mint authorities.
+    #[serde(default)]
+    pub staking_rate: u64,
+}
+
+impl Default for NftContent {
+    fn default() -> Self {
+        Self {
+            ticker: String::new(),
+            remaining: 0,
+            burned: 0,
+            recycle_burns: false,
+            max_supply: None,
+            minted: 0,
+            decimals: None,
+            name: None,
+            image_hash: None,
+            uri: None,
+            mint_authority: None,
+            mint_authorities: Vec::new(),
+            mint_threshold: 0,
+            finalized: false,
+            max_mint_per_tx: None,
+            emission: None,
+            burnable: false,
+            collection: None,
+            description: None,
+            metadata_locked: false,
+            transferable: transferable_by_default(),
+            pausable: false,
+            paused: false,
+            allowlist_root: None,
+            airdrop: None,
+            staking_rate: 0,
+        }
+    }
+}
+
+/// Tokens are transferable unless the NFT opts out, as they were before soulbound tokens.
+const fn transferable_by_default() -> bool {
+    true
+}
+
+/// Amount held by a token charm.
+///
+/// Tokens of an NFT that is not `transferable` are minted in the soulbound form,
+/// which the contract never lets move: the holder can only burn them. Tokens of
+/// an NFT with an `allowlist_root` are minted in the allowlisted form, which only
+/// moves to allowlisted destinations, and tokens of a `pausable` NFT are minted
+/// in the pausable form, which only moves in transactions spending the NFT.
+/// Tokens placed in a [`LimitOrder`] take the offered form, which only moves by
+/// filling the order, and tokens locked in an [`Htlc`] take the locked form,
+/// which only moves by claiming or refunding it.
+/// Tokens under a [`VestingSchedule`] take the vesting form, which only releases
+/// what has vested, and staked tokens take the staked form, which only moves by
+/// unstaking.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TokenAmount {
+    /// Plain amount of transferable tokens
+    Transferable(u64),
+    /// Amount of soulbound tokens
+    Soulbound {
+        /// Number of soulbound tokens
+        soulbound: u64,
+    },
+    /// Amount of tokens restricted to allowlisted destinations
+    Allowlisted {
+        /// Number of allowlisted tokens
+        allowlisted: u64,
+    },
+    /// Amount of tokens whose transfers the NFT holder can pause
+    Pausable {
+        /// Number of pausable tokens
+        pausable: u64,
+    },
+    /// Amount of tokens offered for sale in a limit order
+    Offered {
+        /// Number of offered tokens
+        offered: u64,
+        /// Terms of the order
+        order: Box<LimitOrder>,
+    },
+    /// Amount of tokens locked in a hash time-locked contract
+    Locked {
+        /// Number of locked tokens
+        locked: u64,
+        /// Terms of the lock
+        htlc: Box<Htlc>,
+    },
+    /// Tokens held by a vesting schedule, amounting to its unreleased tokens
+    Vesting {
+        /// Schedule releasing the tokens
+        vesting: Box<VestingSchedule>,
+    },
+    /// Amount of tokens staked for rewards
+    Staked {
+        /// Number of staked tokens
+        staked: u64,
+        /// Block height the stake started at
+        since: u64,
+    },
+}
+
+impl TokenAmount {
+    /// Returns the number of tokens regardless of their form.
+    pub fn amount(&self) -> u64 {
+        match *self {
+            Self::Transferable(amount)
+            | Self::Soulbound { soulbound: amount }
+            | Self::Allowlisted {
+                allowlisted: amount,
+            }
+            | Self::Pausable { pausable: amount }
+            | Self::Offered {
+                offered: amount, ..
+            }
+            | Self::Locked { locked: amount, .. }
+            | Self::Staked { staked: amount, .. } => amount,
+            Self::Vesting { ref vesting } => vesting.unreleased(),
+        }
+    }
+
+    /// Returns the amount in the form tokens minted by the NFT `content` take.
+    pub const fn minted_by(content: &NftContent, amount: u64) -> Self {
+        if !content.transferable {
+            Self::Soulbound { soulbound: amount }
+        } else if content.allowlist_root.is_some() {
+            Self::Allowlisted {
+                allowlisted: amount,
+            }
+        } else if content.pausable {
+            Self::Pausable { pausable: amount }
+        } else {
+            Self::Transferable(amount)
+        }
+    }
+
+    /// Returns whether the tokens are soulbound.
+    pub const fn is_soulbound(&self) -> bool {
+        matches!(self, Self::Soulbound { .. })
+    }
+}
+
+/// Position of an NFT in a collection minted from a single UTXO.
+///
+/// The NFT at `index` has the identity [`collection_identity`] of the spent UTXO
+/// and `index`, and all `size` NFTs of the collection are minted together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionItem {
+    /// Zero-based index of the NFT in the collection
+    pub index: u32,
+    /// Number of NFTs in the collection
+    pub size: u32,
+}
+
+/// Halving emission schedule of the reserve NFT.
+///
+/// Starting at `start_height`, the supply is released in epochs of
+/// `halving_interval` blocks. The first epoch releases `epoch_allowance` tokens
+/// and every following epoch releases half of the previous one, like Bitcoin's
+/// block subsidy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmissionSchedule {
+    /// Block height at which the first epoch starts
+    pub start_height: u64,
+    /// Amount of tokens released by the first epoch
+    pub epoch_allowance: u64,
+    /// Number of blocks per epoch
+    pub halving_interval: u64,
+}
+
+impl EmissionSchedule {
+    /// Returns the cumulative amount of tokens released up to and including `height`.
+    ///
+    /// Every epoch that has started by `height` is released in full.
+    pub fn allowance_at(&self, height: u64) -> u64 {
+        let Some(blocks) = height.checked_sub(self.start_height) else {
+            return 0;
+        };
+        let epochs = blocks
+            .checked_div(self.halving_interval)
+            .map_or(0, |completed| completed.saturating_add(1));
+        (0..epochs.min(u64::from(u64::BITS)))
+            .map(|epoch| self.epoch_allowance >> epoch)
+            .fold(0, u64::saturating_add)
+    }
+}
+
+/// Merkle airdrop committed by the reserve NFT.
+///
+/// `root` commits to the allocations, each an [`airdrop_leaf`] of its index,
+/// destination script and amount. Anyone can mint an allocation to its
+/// destination with a proof in a [`ClaimWitness`], and bit `index` of `claimed`,
+/// least significant bit first, records the claim so it cannot be repeated.
+///
+/// The supply of an NFT with an airdrop is only minted by claims. A claim spends
+/// and recreates the NFT, so the NFT must sit at an output that claimants can
+/// spend, and its `remaining` must cover the allocations. As anyone can spend it,
+/// the NFT is always recreated at the script it was spent from, and it can never
+/// be finalized, paused, have its metadata updated or be burned before its
+/// reserve is exhausted. Claims respect `max_mint_per_tx`, while an NFT with an
+/// airdrop cannot have an `emission` schedule.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Airdrop {
+    /// Merkle root of the allocation leaves
+    pub root: B32,
+    /// Bitmap of the claimed allocation indices
+    #[serde(default)]
+    pub claimed: Vec<u8>,
+}
+
+impl Airdrop {
+    /// Returns whether the allocation at `index` has been claimed.
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed
+            .get(index as usize / 8)
+            .is_some_and(|byte| byte >> (index % 8) & 1 == 1)
+    }
+
+    /// Marks the allocation at `index` as claimed.
+    pub fn set_claimed(&mut self, index: u32) {
+        let byte = index as usize / 8;
+        if self.claimed.len() <= byte {
+            self.claimed.resize(byte + 1, 0);
+        }
+        self.claimed[byte] |= 1 << (index % 8);
+    }
+}
+
+/// Witness authorizing a token mint from an NFT with a `mint_authority` or
+/// `mint_authorities`, or proving the minted tokens go to its allowlist.
+///
+/// Supplied as the private input `w` of both the NFT and the token app of a
+/// mint spell.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MintWitness {
+    /// Hex-encoded 64-byte BIP-340 Schnorr signature over [`mint_message`]
+    #[serde(default)]
+    pub signature: String,
+    /// Signatures by members of the mint multisig
+    #[serde(default)]
+    pub signatures: Vec<MultisigSignature>,
+    /// Allowlist inclusion proofs by output index, if the NFT has an `allowlist_root`
+    #[serde(default)]
+    pub allowlist_proofs: BTreeMap<usize, Vec<B32>>,
+}
+
+/// Witness of a transfer of allowlisted tokens.
+///
+/// Supplied as the private input `w` of the token app of a transfer spell.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferWitness {
+    /// Allowlist inclusion proofs by output index
+    #[serde(default)]
+    pub allowlist_proofs: BTreeMap<usize, Vec<B32>>,
+}
+
+/// Witness of an airdrop claim.
+///
+/// Supplied as the private input `w` of both the NFT and the token app of a
+/// claim spell.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimWitness {
+    /// Allocations claimed by the spell
+    pub claims: Vec<AirdropClaim>,
+    /// Allowlist inclusion proofs by output index, if the NFT has an `allowlist_root`
+    #[serde(default)]
+    pub allowlist_proofs: BTreeMap<usize, Vec<B32>>,
+}
+
+/// One allocation claimed from an [`Airdrop`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropClaim {
+    /// Index of the allocation in the airdrop
+    pub index: u32,
+    /// Index of the transaction output receiving the allocation
+    pub output: usize,
+    /// Amount of the allocation
+    pub amount: u64,
+    /// Merkle proof of the allocation leaf
+    pub proof: Vec<B32>,
+}
+
+/// A signature over [`mint_message`] by one member of the mint multisig.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigSignature {
+    /// x-only public key of the signer, one of the NFT `mint_authorities`
+    pub signer: B32,
+    /// Hex-encoded 64-byte BIP-340 Schnorr signature
+    pub signature: String,
+}
+
+/// Public input `x` declaring the operation a spell performs.
+///
+/// An empty `x` leaves the contract to infer the operation from the shape of
+/// the transaction, which is how spells without a public input are validated.
+/// An operation declaring a block height must prove it with `height_proof`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInput {
+    /// Encoding version, must be [`PUBLIC_INPUT_VERSION`]
+    pub version: u32,
+    /// Operation the spell performs
+    pub op: ContractInput,
+    /// Timelocked input proving the block height declared by `op`
+    #[serde(default)]
+    pub height_proof: Option<HeightProof>,
+}
+
+/// Operation declared by a spell in its [`PublicInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractInput {
+    /// Mint a new NFT, or mint tokens against the supply of a spent NFT
+    Mint {
+        /// Block height the spell is confirmed above, required by an emission schedule
+        height: Option<u64>,
+    },
+    /// Transfer tokens, or move the NFT with its state unchanged
+    Transfer,
+    /// Burn tokens against the supply of a spent NFT, or burn the NFT itself
+    Burn,
+    /// Update the NFT metadata
+    UpdateMetadata,
+    /// Claim airdrop allocations from the reserve of a spent NFT, or the tokens
+    /// of a spent [`Htlc`] with its preimage
+    Claim,
+    /// Swap tokens against the [`SwapOrder`] in the witness
+    Swap,
+    /// Fill or cancel the [`LimitOrder`] spent by the transaction
+    Fill,
+    /// Refund the [`Htlc`] spent by the transaction after its timeout
+    Refund {
+        /// Block height the spell is confirmed above, checked against the HTLC timeout
+        height: u64,
+    },
+    /// Release vested tokens from the [`VestingSchedule`] spent by the transaction
+    Release {
+        /// Block height the spell is confirmed above, which determines the vested amount
+        height: u64,
+    },
+    /// Stake tokens, starting their lock at the declared height
+    Stake {
+        /// Block height the spell is confirmed at, recorded by the staked outputs
+        height: u64,
+    },
+    /// Unstake the staked tokens spent by the transaction, minting their reward
+    /// from the reserve of the NFT if it is spent
+    Unstake {
+        /// Block height the spell is confirmed above, which determines the reward
+        height: u64,
+    },
 }
 
+impl ContractInput {
+    /// Returns the block height the operation declares, which the
+    /// [`PublicInput`] must prove.
+    pub const fn height(&self) -> Option<u64> {
+        match *self {
+            Self::Mint { height } => height,
+            Self::Refund { height } | Self::Release { height } | Self::Unstake { height } => {
+                Some(height)
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Version of the [`PublicInput`] encoding understood by this contract.
+pub const PUBLIC_INPUT_VERSION: u32 = 1;
+
+/// Domain separation tag prefixed to every [`mint_message`].
+const MINT_MESSAGE_TAG: &[u8] = b"my-token/mint/v2";
+
+/// Maximum number of decimal places an NFT may declare for its token.
+pub const MAX_DECIMALS: u8 = 18;
+
+/// Maximum length of the token name in bytes.
+pub const MAX_NAME_LEN: usize = 64;
+
+/// Maximum length of the token description URI in bytes.
+pub const MAX_URI_LEN: usize = 256;
+
+/// Maximum length of the token description in bytes.
+pub const MAX_DESCRIPTION_LEN: usize = 1024;
+
+/// Minimum length of a ticker in characters.
+pub const MIN_TICKER_LEN: usize = 1;
+
+/// Maximum length of a ticker in characters.
+pub const MAX_TICKER_LEN: usize = 16;
+
+/// Punctuation allowed in tickers besides uppercase ASCII letters and digits.
+///
+/// Together with [`MIN_TICKER_LEN`] and [`MAX_TICKER_LEN`] this defines the ticker
+/// grammar. Changing any of them changes the app binary and thus its verification key.
+pub const TICKER_PUNCTUATION: &[char] = &['-'];
+
+/// Reasons a transaction can fail to satisfy the contract.
+///
+/// Each variant names one rejection path of [`validate`], so callers (and
+/// `charms spell check` logs) can tell exactly which rule a spell broke.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractError {
+    /// The app tag is neither [`NFT`] nor [`TOKEN`].
+    UnknownTag(char),
+    /// The public input `x` must be empty or a [`PublicInput`].
+    InvalidPublicInput,
+    /// The [`PublicInput`] version is not [`PUBLIC_INPUT_VERSION`].
+    UnsupportedInputVersion(u32),
+    /// The declared operation is not supported by this contract version.
+    UnsupportedOperation,
+    /// The witness `w` does not contain a string.
+    WitnessNotString,
+    /// The witness string is not a valid UTXO ID (`txid:index`).
+    InvalidWitnessUtxoId(String),
+    /// The hash of the witness does not match the NFT identity.
+    IdentityMismatch,
+    /// The transaction does not spend the UTXO named in the witness.
+    WitnessUtxoNotSpent,
+    /// An NFT mint must create exactly one NFT; holds the number created.
+    NftCountMismatch(usize),
+    /// A spent NFT is recreated more than once; holds the number of copies.
+    DuplicateOutputNft(usize),
+    /// The NFT state could not be read as [`NftContent`].
+    InvalidNftContent,
+    /// The managing NFT is not present in the transaction inputs.
+    MissingInputNft,
+    /// The managing NFT is not present in the transaction outputs.
+    MissingOutputNft,
+    /// The NFT state changed in a transaction that is not a mint.
+    NftStateChanged,
+    /// A token amount could not be read as a `u64`.
+    InvalidTokenAmount,
+    /// The total token amount of the inputs or outputs overflows a `u64`.
+    TokenAmountOverflow,
+    /// A transfer must move a non-zero amount of tokens.
+    EmptyTransfer,
+    /// Token inputs and outputs of a transfer are not balanced.
+    UnbalancedTransfer {
+        /// Total token amount in the inputs
+        input: u64,
+        /// Total token amount in the outputs
+        output: u64,
+    },
+    /// The NFT remaining supply increased.
+    SupplyIncreased {
+        /// Remaining supply in the input NFT
+        incoming: u64,
+        /// Remaining supply in the output NFT
+        outgoing: u64,
+    },
+    /// A mint takes from the NFT supply but holds fewer output tokens than input tokens.
+    MintUnderflow {
+        /// Total token amount in the inputs
+        input: u64,
+        /// Total token amount in the outputs
+        output: u64,
+    },
+    /// The minted token amount differs from the decrease in NFT supply.
+    MintAmountMismatch {
+        /// Tokens created by the transaction
+        minted: u64,
+        /// Decrease in the NFT remaining supply
+        supply_decrease: u64,
+    },
+    /// The NFT remaining supply after a burn is not what the burn mode requires.
+    BurnRemainingMismatch {
+        /// Remaining supply required by the burn
+        expected: u64,
+        /// Remaining supply in the output NFT
+        actual: u64,
+    },
+    /// The NFT burned counter after a burn does not account for the burned tokens.
+    BurnedCounterMismatch {
+        /// Burned counter required by the burn
+        expected: u64,
+        /// Burned counter in the output NFT
+        actual: u64,
+    },
+    /// The NFT minted counter after a supply change does not account for the change.
+    MintedCounterMismatch {
+        /// Minted counter required by the supply change
+        expected: u64,
+        /// Minted counter in the output NFT
+        actual: u64,
+    },
+    /// The NFT breaks the `minted + remaining == max_supply` invariant.
+    SupplyInvariantViolated {
+        /// Total supply recorded in the NFT
+        max_supply: u64,
+        /// Minted counter in the NFT
+        minted: u64,
+        /// Remaining supply in the NFT
+        remaining: u64,
+    },
+    /// Updating the NFT supply accounting would overflow or underflow a `u64`.
+    SupplyOverflow,
+    /// The NFT declares more than [`MAX_DECIMALS`] decimal places.
+    TooManyDecimals(u8),
+    /// The token name is longer than [`MAX_NAME_LEN`] bytes; holds its length.
+    NameTooLong(usize),
+    /// The token URI is longer than [`MAX_URI_LEN`] bytes; holds its length.
+    UriTooLong(usize),
+    /// The token description is longer than [`MAX_DESCRIPTION_LEN`] bytes; holds its length.
+    DescriptionTooLong(usize),
+    /// The metadata of the NFT is locked and cannot be updated.
+    MetadataLocked,
+    /// Soulbound tokens are spent into new token outputs instead of being burned.
+    NonTransferable,
+    /// Token outputs are not in the form the NFT `transferable` and `pausable`
+    /// flags and `allowlist_root` require (see [`TokenAmount::minted_by`]).
+    TokenFormMismatch,
+    /// Tokens are transferred while the NFT is `paused` without spending the NFT.
+    TransfersPaused,
+    /// Allowlisted tokens are transferred without spending or referencing the NFT.
+    AllowlistUnavailable,
+    /// Pausable tokens are transferred without spending the NFT.
+    PauseUnavailable,
+    /// Transfers are paused or resumed on an NFT that is not `pausable`.
+    NotPausable,
+    /// The transaction does not expose the destination script of this output.
+    MissingDestination(usize),
+    /// The witness has no allowlist proof for the tokens in this output.
+    MissingAllowlistProof(usize),
+    /// The destination script of this output is not in the allowlist.
+    NotAllowlisted(usize),
+    /// Allocations are claimed from an NFT without an airdrop.
+    MissingAirdrop,
+    /// Tokens are minted outside claims from an NFT with an airdrop.
+    AirdropOnly,
+    /// The NFT of an airdrop is finalized, paused, or has its metadata updated.
+    AirdropNftLocked,
+    /// The NFT of an airdrop is not recreated at the script it was spent from.
+    AirdropNftMoved,
+    /// The witness of an airdrop claim is not a [`ClaimWitness`].
+    MissingClaimWitness,
+    /// The allocation at this index has already been claimed.
+    AlreadyClaimed(u32),
+    /// The allocation at this index is not in the airdrop or not paid as committed.
+    InvalidClaim(u32),
+    /// The witness of a swap is not a valid [`SwapOrder`].
+    InvalidSwapOrder,
+    /// The tokens given to the taker differ from the swap order.
+    SwapGiveMismatch {
+        /// Tokens the order gives
+        order: u64,
+        /// Tokens given by the transaction
+        given: u64,
+    },
+    /// The maker receives less than the swap order wants.
+    SwapUnderpaid {
+        /// Amount the order wants
+        wanted: u64,
+        /// Amount paid to the maker
+        paid: u64,
+    },
+    /// The transaction spends no limit order, or more than one; holds their count.
+    OrderCountMismatch(usize),
+    /// The limit order is not signed by its maker.
+    InvalidOrderSignature,
+    /// More tokens are carried under the order than it offers.
+    InvalidOrderRemainder {
+        /// Tokens the spent order offers
+        offered: u64,
+        /// Tokens carried under the order
+        remainder: u64,
+    },
+    /// The maker is paid less than the order price of the filled tokens.
+    OrderUnderpaid {
+        /// Price of the filled tokens in sats
+        price: u64,
+        /// Sats paid to the maker
+        paid: u64,
+    },
+    /// The transaction spends no HTLC, or more than one; holds their count.
+    HtlcCountMismatch(usize),
+    /// The witness does not reveal the preimage of the HTLC payment hash.
+    InvalidPreimage,
+    /// The HTLC is refunded before its timeout.
+    HtlcNotExpired {
+        /// Block height after which the HTLC can be refunded
+        timeout_height: u64,
+    },
+    /// Fewer tokens reach the claim or refund destination than the HTLC locks.
+    HtlcPayoutMismatch {
+        /// Tokens locked in the HTLC
+        locked: u64,
+        /// Tokens paid to the destination
+        paid: u64,
+    },
+    /// The transaction spends no vesting schedule, or more than one; holds their count.
+    VestingCountMismatch(usize),
+    /// The vesting schedule is not re-emitted with only `released` advanced.
+    VestingStateChanged,
+    /// More tokens are released than have vested.
+    VestingExceeded {
+        /// Tokens vested at the declared height
+        vested: u64,
+        /// Tokens released after the transaction
+        released: u64,
+    },
+    /// Fewer tokens reach the beneficiary than the transaction releases.
+    VestingPayoutMismatch {
+        /// Tokens released by the transaction
+        released: u64,
+        /// Tokens paid to the beneficiary
+        paid: u64,
+    },
+    /// Staked outputs are created without declaring a stake.
+    UndeclaredStake,
+    /// A stake starts at another height than the declared one, or after the unstake.
+    InvalidStakeHeight {
+        /// Block height declared by the spell
+        height: u64,
+        /// Block height the stake starts at
+        since: u64,
+    },
+    /// The transaction spends no staked tokens.
+    MissingStakeInput,
+    /// The tokens minted by an unstake differ from the staking reward.
+    StakeRewardMismatch {
+        /// Reward earned by the unstaked tokens
+        reward: u64,
+        /// Tokens minted by the transaction
+        minted: u64,
+    },
+    /// The staking reward exceeds the remaining supply of the reserve.
+    RewardExceedsReserve {
+        /// Reward earned by the unstaked tokens
+        reward: u64,
+        /// Supply remaining in the reserve
+        remaining: u64,
+    },
+    /// The tokens minted by a claim differ from the claimed allocations.
+    ClaimAmountMismatch {
+        /// Tokens minted by the transaction
+        minted: u64,
+        /// Total amount of the claimed allocations
+        claimed: u64,
+    },
+    /// The ticker is not between [`MIN_TICKER_LEN`] and [`MAX_TICKER_LEN`] characters;
+    /// holds its length.
+    InvalidTickerLength(usize),
+    /// The ticker contains a character outside the ticker grammar.
+    InvalidTickerChar(char),
+    /// The mint authority is not a valid BIP-340 public key.
+    InvalidMintAuthority,
+    /// The NFT has a mint authority but the witness carries no [`MintWitness`].
+    MissingMintSignature,
+    /// The mint signature is not 64 hex-encoded bytes.
+    InvalidSignatureEncoding,
+    /// The mint signature does not verify against the mint authority.
+    InvalidMintSignature,
+    /// The mint threshold is not between 1 and the number of mint authorities,
+    /// or is set without any mint authorities.
+    InvalidMintThreshold {
+        /// Required number of signatures
+        threshold: u32,
+        /// Number of mint authorities
+        authorities: usize,
+    },
+    /// A key appears more than once among the mint authorities.
+    DuplicateMintAuthority,
+    /// A multisig signature is by a key that is not one of the mint authorities.
+    UnknownSigner,
+    /// A mint authority signed more than once.
+    DuplicateSigner,
+    /// Fewer mint authorities signed than the mint threshold requires.
+    ThresholdNotMet {
+        /// Required number of signatures
+        threshold: u32,
+        /// Number of valid distinct signatures
+        signers: usize,
+    },
+    /// The NFT is finalized and can no longer mint tokens.
+    SupplyFinalized,
+    /// The collection index is not below the collection size.
+    InvalidCollectionIndex {
+        /// Index committed in the NFT
+        index: u32,
+        /// Size committed in the NFT
+        size: u32,
+    },
+    /// The collection mint does not output the NFT at this index.
+    MissingCollectionItem(u32),
+    /// The collection mint outputs the NFT at this index more than once.
+    DuplicateCollectionItem(u32),
+    /// The NFT at this index does not commit to its position in the collection.
+    CollectionItemMismatch(u32),
+    /// A parent collection NFT has no name, no room for children, or starts with
+    /// children already minted.
+    InvalidCollection,
+    /// The parent collection NFT would exceed its `max_items`.
+    CollectionFull {
+        /// Maximum number of children of the collection
+        max_items: u32,
+    },
+    /// A child NFT is minted without spending and recreating its parent.
+    MissingParentNft,
+    /// The NFT is burned while tokens remain in its reserve and it is not
+    /// `burnable`, or has an airdrop.
+    NftNotBurnable {
+        /// Supply left in the reserve
+        remaining: u64,
+    },
+    /// The NFT is burned although its pausable or allowlisted tokens need it to move.
+    NftStillRequired,
+    /// The transaction mints more than the NFT `max_mint_per_tx` allows.
+    MintCapExceeded {
+        /// Tokens minted by the transaction
+        minted: u64,
+        /// Maximum amount a single transaction may mint
+        cap: u64,
+    },
+    /// The emission schedule has no `halving_interval` or the NFT does not track
+    /// its `max_supply`.
+    InvalidEmissionSchedule,
+    /// A mint under an emission schedule does not declare its block height.
+    MissingBlockHeight,
+    /// The public input declares a block height without a [`HeightProof`].
+    MissingHeightProof,
+    /// The [`HeightProof`] does not reveal a block height lock of the input it names.
+    InvalidHeightProof,
+    /// The declared block height exceeds the one the [`HeightProof`] locks to.
+    HeightNotProven {
+        /// Block height declared by the spell
+        height: u64,
+        /// Block height the timelocked input is locked to
+        proven: u64,
+    },
+    /// The cumulative amount minted exceeds what the emission schedule has released.
+    EmissionExceeded {
+        /// Cumulative amount minted after the transaction
+        minted: u64,
+        /// Cumulative amount released at the block height
+        allowed: u64,
+    },
+}
+
+impl fmt::Display for ContractError {
+    #[allow(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTag(tag) => write!(f, "unknown app tag '{tag}'"),
+            Self::InvalidPublicInput => {
+                write!(f, "public input must be empty or a versioned operation")
+            },
+            Self::UnsupportedInputVersion(version) => {
+                write!(f, "unsupported public input version {version}")
+            },
+            Self::UnsupportedOperation => write!(f, "operation is not supported"),
+            Self::WitnessNotString => write!(f, "witness must be a UTXO ID string"),
+            Self::InvalidWitnessUtxoId(w) => write!(f, "witness '{w}' is not a valid UTXO ID"),
+            Self::IdentityMismatch => write!(f, "hash of the witness does not match NFT identity"),
+            Self::WitnessUtxoNotSpent => write!(f, "witness UTXO is not spent by the transaction"),
+            Self::NftCountMismatch(count) => {
+                write!(f, "must mint exactly one NFT, found {count}")
+            },
+            Self::DuplicateOutputNft(count) => {
+                write!(f, "spent NFT must be recreated at most once, found {count} copies")
+            },
+            Self::InvalidNftContent => write!(f, "NFT state is not valid NftContent"),
+            Self::MissingInputNft => write!(f, "managing NFT not found in inputs"),
+            Self::MissingOutputNft => write!(f, "managing NFT not found in outputs"),
+            Self::NftStateChanged => write!(f, "NFT state must be preserved"),
+            Self::InvalidTokenAmount => write!(f, "token amount is not a valid u64"),
+            Self::TokenAmountOverflow => write!(f, "total token amount overflows u64"),
+            Self::EmptyTransfer => write!(f, "transfer must move a non-zero token amount"),
+            Self::UnbalancedTransfer { input, output } => {
+                write!(f, "token inputs ({input}) must equal token outputs ({output})")
+            },
+            Self::SupplyIncreased { incoming, outgoing } => write!(
+                f,
+                "incoming remaining supply ({incoming}) must be >= outgoing remaining supply \
+                 ({outgoing})"
+            ),
+            Self::MintUnderflow { input, output } => write!(
+                f,
+                "mint outputs ({output}) must not hold fewer tokens than its inputs ({input})"
+            ),
+            Self::MintAmountMismatch {
+                minted,
+                supply_decrease,
+            } => write!(
+                f,
+                "minted amount ({minted}) must equal the decrease in remaining supply \
+                 ({supply_decrease})"
+            ),
+            Self::BurnRemainingMismatch { expected, actual } => {
+                write!(f, "remaining supply after burn must be {expected}, found {actual}")
+            },
+            Self::BurnedCounterMismatch { expected, actual } => {
+                write!(f, "burned counter after burn must be {expected}, found {actual}")
+            },
+            Self::MintedCounterMismatch { expected, actual } => {
+                write!(f, "minted counter must be {expected}, found {actual}")
+            },
+            Self::SupplyInvariantViolated {
+                max_supply,
+                minted,
+                remaining,
+            } => write!(
+                f,
+                "minted ({minted}) + remaining ({remaining}) must equal max supply ({max_supply})"
+            ),
+            Self::SupplyOverflow => write!(f, "NFT supply accounting is out of u64 range"),
+            Self::TooManyDecimals(decimals) => {
+                write!(f, "decimals ({decimals}) must be at most {MAX_DECIMALS}")
+            },
+            Self::NameTooLong(len) => {
+                write!(f, "name length ({len}) must be at most {MAX_NAME_LEN} bytes")
+            },
+            Self::UriTooLong(len) => {
+                write!(f, "uri length ({len}) must be at most {MAX_URI_LEN} bytes")
+            },
+            Self::DescriptionTooLong(len) => {
+                write!(f, "description length ({len}) must be at most {MAX_DESCRIPTION_LEN} bytes")
+            },
+            Self::MetadataLocked => write!(f, "NFT metadata is locked"),
+            Self::NonTransferable => write!(f, "soulbound tokens can only be burned"),
+            Self::TransfersPaused => {
+                write!(f, "token transfers are paused; the NFT must be spent to approve them")
+            },
+            Self::AllowlistUnavailable => {
+                write!(f, "allowlisted tokens can only move when the NFT is spent or referenced")
+            },
+            Self::PauseUnavailable => {
+                write!(f, "pausable tokens can only move when the NFT is spent")
+            },
+            Self::NotPausable => write!(f, "transfers of the NFT's tokens cannot be paused"),
+            Self::MissingDestination(output) => {
+                write!(f, "destination script of output #{output} is unknown")
+            },
+            Self::MissingAllowlistProof(output) => {
+                write!(f, "missing allowlist proof for output #{output}")
+            },
+            Self::NotAllowlisted(output) => {
+                write!(f, "destination of output #{output} is not allowlisted")
+            },
+            Self::InvalidSwapOrder => write!(f, "witness is not a valid swap order"),
+            Self::SwapGiveMismatch { order, given } => {
+                write!(f, "swap gives {given} tokens but the order gives {order}")
+            },
+            Self::SwapUnderpaid { wanted, paid } => {
+                write!(f, "swap pays the maker {paid} but the order wants {wanted}")
+            },
+            Self::OrderCountMismatch(count) => {
+                write!(f, "exactly one limit order must be spent, found {count}")
+            },
+            Self::InvalidOrderSignature => write!(f, "limit order is not signed by its maker"),
+            Self::InvalidOrderRemainder { offered, remainder } => write!(
+                f,
+                "order remainder ({remainder}) must not exceed the offered amount ({offered})"
+            ),
+            Self::OrderUnderpaid { price, paid } => {
+                write!(f, "fill pays the maker {paid} sats but costs {price}")
+            },
+            Self::HtlcCountMismatch(count) => {
+                write!(f, "exactly one HTLC must be spent, found {count}")
+            },
+            Self::InvalidPreimage => write!(f, "witness must reveal the HTLC preimage"),
+            Self::HtlcNotExpired { timeout_height } => {
+                write!(f, "HTLC can only be refunded after height {timeout_height}")
+            },
+            Self::HtlcPayoutMismatch { locked, paid } => {
+                write!(f, "HTLC locks {locked} tokens but pays out {paid}")
+            },
+            Self::VestingCountMismatch(count) => {
+                write!(f, "exactly one vesting schedule must be spent, found {count}")
+            },
+            Self::VestingStateChanged => {
+                write!(f, "vesting schedule must be re-emitted with only released advanced")
+            },
+            Self::VestingExceeded { vested, released } => {
+                write!(f, "released amount ({released}) exceeds the vested amount ({vested})")
+            },
+            Self::VestingPayoutMismatch { released, paid } => {
+                write!(f, "release of {released} tokens pays the beneficiary {paid}")
+            },
+            Self::UndeclaredStake => write!(f, "staked outputs require a declared stake"),
+            Self::InvalidStakeHeight { height, since } => {
+                write!(f, "stake since height {since} is invalid at height {height}")
+            },
+            Self::MissingStakeInput => write!(f, "the transaction spends no staked tokens"),
+            Self::StakeRewardMismatch { reward, minted } => {
+                write!(f, "unstake mints {minted} tokens but earns a reward of {reward}")
+            },
+            Self::RewardExceedsReserve { reward, remaining } => {
+                write!(f, "staking reward ({reward}) exceeds the remaining supply ({remaining})")
+            },
+            Self::MissingAirdrop => write!(f, "the NFT has no airdrop to claim from"),
+            Self::AirdropOnly => write!(f, "the NFT supply can only be minted by airdrop claims"),
+            Self::AirdropNftLocked => {
+                write!(f, "the NFT of an airdrop can only be spent to claim allocations")
+            },
+            Self::AirdropNftMoved => {
+                write!(f, "the NFT of an airdrop must stay at the script it was spent from")
+            },
+            Self::MissingClaimWitness => write!(f, "witness is not a valid airdrop claim"),
+            Self::AlreadyClaimed(index) => {
+                write!(f, "airdrop allocation #{index} has already been claimed")
+            },
+            Self::InvalidClaim(index) => {
+                write!(f, "airdrop allocation #{index} is not in the airdrop as claimed")
+            },
+            Self::ClaimAmountMismatch { minted, claimed } => {
+                write!(f, "minted amount ({minted}) must equal the claimed allocations ({claimed})")
+            },
+            Self::TokenFormMismatch => {
+                write!(f, "token outputs are not in the form the NFT requires")
+            },
+            Self::InvalidTickerLength(len) => write!(
+                f,
+                "ticker length ({len}) must be between {MIN_TICKER_LEN} and {MAX_TICKER_LEN}"
+            ),
+            Self::InvalidTickerChar(c) => write!(
+                f,
+                "ticker character {c:?} must be an uppercase ASCII letter, a digit or one of \
+                 {TICKER_PUNCTUATION:?}"
+            ),
+            Self::InvalidMintAuthority => write!(f, "mint authority is not a valid public key"),
+            Self::MissingMintSignature => write!(f, "witness must carry the mint signature"),
+            Self::InvalidSignatureEncoding => {
+                write!(f, "signature must be 64 hex-encoded bytes")
+            },
+            Self::InvalidMintSignature => {
+                write!(f, "mint signature does not verify against the mint authority")
+            },
+            Self::InvalidMintThreshold {
+                threshold,
+                authorities,
+            } => write!(
+                f,
+                "mint threshold ({threshold}) must be between 1 and the number of mint \
+                 authorities ({authorities})"
+            ),
+            Self::DuplicateMintAuthority => write!(f, "mint authorities must be distinct"),
+            Self::UnknownSigner => write!(f, "signer is not one of the mint authorities"),
+            Self::DuplicateSigner => write!(f, "mint authority signed more than once"),
+            Self::ThresholdNotMet { threshold, signers } => {
+                write!(f, "mint requires {threshold} mint authority signatures, found {signers}")
+            },
+            Self::SupplyFinalized => write!(f, "NFT is finalized and can no longer mint"),
+            Self::InvalidCollectionIndex { index, size } => {
+                write!(f, "collection index {index} is out of range for size {size}")
+            },
+            Self::MissingCollectionItem(index) => write!(f, "collection NFT #{index} is missing"),
+            Self::DuplicateCollectionItem(index) => {
+                write!(f, "collection NFT #{index} is minted more than once")
+            },
+            Self::CollectionItemMismatch(index) => {
+                write!(f, "collection NFT #{index} does not commit to its position")
+            },
+            Self::InvalidCollection => {
+                write!(f, "collection NFT needs a name and room for items, and must start empty")
+            },
+            Self::CollectionFull { max_items } => {
+                write!(f, "collection cannot hold more than {max_items} items")
+            },
+            Self::MissingParentNft => {
+                write!(f, "child NFT must be minted by spending its parent collection NFT")
+            },
+            Self::NftNotBurnable { remaining } => {
+                write!(f, "NFT cannot be burned with {remaining} tokens remaining")
+            },
+            Self::NftStillRequired => {
+                write!(f, "NFT cannot be burned while its tokens need it to move")
+            },
+            Self::MintCapExceeded { minted, cap } => {
+                write!(f, "minted amount ({minted}) exceeds the per-transaction cap ({cap})")
+            },
+            Self::InvalidEmissionSchedule => {
+                write!(f, "emission schedule needs a halving interval and a tracked max supply")
+            },
+            Self::MissingBlockHeight => {
+                write!(f, "public input must carry the block height to mint")
+            },
+            Self::MissingHeightProof => {
+                write!(f, "declared block height needs a timelocked input to prove it")
+            },
+            Self::InvalidHeightProof => {
+                write!(f, "height proof does not reveal a block height lock of its input")
+            },
+            Self::HeightNotProven { height, proven } => {
+                write!(f, "declared height {height} exceeds the proven height {proven}")
+            },
+            Self::EmissionExceeded { minted, allowed } => write!(
+                f,
+                "cumulative minted amount ({minted}) exceeds the emission schedule ({allowed})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
+
 /// Main contract validation function.
 ///
 /// This function serves as the entry point for contract validation, routing to
 /// appropriate validation logic based on the application tag (NFT or TOKEN).
+/// It is a thin wrapper around [`validate`] that logs the rejection reason.
 ///
 /// # Arguments
 ///
 /// * `app` - The application context containing tag, identity, and verification key
 /// * `tx` - The transaction to validate
-/// * `x` - Additional data (must be empty for this contract)
-/// * `w` - Witness data used for NFT minting validation
+/// * `x` - Public input: empty, or the [`PublicInput`] declaring the operation
+/// * `w` - Witness data used for NFT and token minting validation
 ///
 /// # Returns
 ///
-/// Returns `true` if the contract is satisfied, panics otherwise via the `check!` macro.
+/// Returns `true` if the contract is satisfied, `false` otherwise.
+pub fn app_contract(app: &App, tx: &Transaction, x: &Data, w: &Data) -> bool {
+    match validate(app, tx, x, w) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("contract not satisfied: {err}");
+            false
+        },
+    }
+}
+
+/// Validates a transaction against the contract.
 ///
-/// # Panics
+/// Routes to the NFT or token rules based on the application tag and reports
+/// the first rule the transaction breaks. With a [`PublicInput`] only the rules
+/// of the declared operation apply, otherwise the operation is inferred.
 ///
-/// Panics if `x` is not empty or if contract validation fails.
-pub fn app_contract(app: &App, tx: &Transaction, x: &Data, w: &Data) -> bool {
-    let empty = Data::empty();
-    assert_eq!(x, &empty);
-    match app.tag {
-        NFT => {
-            check!(nft_contract_satisfied(app, tx, w));
+/// # Arguments
+///
+/// * `app` - The application context containing tag, identity, and verification key
+/// * `tx` - The transaction to validate
+/// * `x` - Public input: empty, or the [`PublicInput`] declaring the operation
+/// * `w` - Witness data used for NFT and token minting validation
+///
+/// # Errors
+///
+/// Returns the [`ContractError`] describing why the transaction was rejected.
+pub fn validate(app: &App, tx: &Transaction, x: &Data, w: &Data) -> Result<(), ContractError> {
+    let op = if x.is_empty() {
+        None
+    } else {
+        let input: PublicInput = x.value().map_err(|_| ContractError::InvalidPublicInput)?;
+        if input.version != PUBLIC_INPUT_VERSION {
+            return Err(ContractError::UnsupportedInputVersion(input.version));
+        }
+        // A declared height is only as good as the timelock proving it.
+        if let Some(height) = input.op.height() {
+            timelock::check_height(tx, input.height_proof.as_ref(), height)?;
+        }
+        Some(input.op)
+    };
+    // Collection parents and children follow rules of their own.
+    if app.tag == NFT && collection::is_collection_nft(app, tx) {
+        return collection::collection_nft_satisfied(app, tx, w, op);
+    }
+    // An airdrop NFT anyone can spend stays where claimants find it.
+    if app.tag == NFT {
+        check_airdrop_custody(app, tx)?;
+    }
+    // Soulbound tokens can only be burned, whatever else the transaction does.
+    if app.tag == TOKEN && has_soulbound_input(app, tx)? {
+        return can_burn_soulbound(app, tx);
+    }
+    // Offered, locked, vesting and staked tokens only move under their own terms,
+    // and a transaction spends at most one kind of them.
+    if app.tag == TOKEN {
+        if staking::has_stake_output(app, tx)? && !matches!(op, Some(ContractInput::Stake { .. })) {
+            return Err(ContractError::UndeclaredStake);
+        }
+        let spent = (
+            orders::has_order_input(app, tx)?,
+            htlc::has_htlc_input(app, tx)?,
+            vesting::has_vesting_input(app, tx)?,
+            staking::has_stake_input(app, tx)?,
+        );
+        match (spent, op) {
+            ((false, false, false, false), _) => {},
+            ((true, false, false, false), None | Some(ContractInput::Fill)) => {
+                return orders::can_fill_order(app, tx, w);
+            },
+            ((false, true, false, false), None | Some(ContractInput::Claim)) => {
+                return htlc::can_unlock_htlc(app, tx, w, None);
+            },
+            ((false, true, false, false), Some(ContractInput::Refund { height })) => {
+                return htlc::can_unlock_htlc(app, tx, w, Some(height));
+            },
+            ((false, false, true, false), None) => {
+                return vesting::can_release_vested(app, tx, w, None);
+            },
+            ((false, false, true, false), Some(ContractInput::Release { height })) => {
+                return vesting::can_release_vested(app, tx, w, Some(height));
+            },
+            ((false, false, false, true), None) => {
+                return staking::can_unstake(app, tx, w, None);
+            },
+            ((false, false, false, true), Some(ContractInput::Unstake { height })) => {
+                return staking::can_unstake(app, tx, w, Some(height));
+            },
+            _ => return Err(ContractError::UnsupportedOperation),
+        }
+    }
+    match (op, app.tag) {
+        (Some(op), _) => operation_satisfied(app, tx, w, op),
+        (None, NFT) => nft_contract_satisfied(app, tx, w),
+        (None, TOKEN) => token_contract_satisfied(app, tx, w),
+        (None, tag) => Err(ContractError::UnknownTag(tag)),
+    }
+}
+
+/// Validates a transaction against the rules of the declared operation.
+///
+/// # Arguments
+///
+/// * `app` - The NFT or token application context
+/// * `tx` - The transaction to validate
+/// * `w` - Witness data used for NFT and token minting validation
+/// * `op` - The operation declared in the public input
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the transaction performs the declared operation.
+///
+fn operation_satisfied(
+    app: &App,
+    tx: &Transaction,
+    w: &Data,
+    op: ContractInput,
+) -> Result<(), ContractError> {
+    let token_app = match app.tag {
+        NFT => token_app_for(app),
+        TOKEN => app.clone(),
+        tag => return Err(ContractError::UnknownTag(tag)),
+    };
+    match op {
+        ContractInput::Mint { .. } if app.tag == NFT && input_nft_content(app, tx).is_err() => {
+            can_mint_nft(app, tx, w)
+        },
+        ContractInput::Mint { height } => can_mint_token(&token_app, tx, w, height),
+        ContractInput::Transfer if app.tag == NFT => can_preserve_nft(app, tx),
+        ContractInput::Burn if app.tag == NFT && !has_output_nft(app, tx) => can_burn_nft(app, tx),
+        ContractInput::Transfer => can_transfer_token(app, tx, w),
+        ContractInput::Burn => can_burn_token(&token_app, tx),
+        ContractInput::UpdateMetadata if app.tag == NFT => can_update_metadata(app, tx),
+        ContractInput::Claim => can_claim_airdrop(&token_app, tx, w),
+        ContractInput::Swap if app.tag == TOKEN => swap::can_swap_token(app, tx, w),
+        ContractInput::Fill if app.tag == TOKEN => orders::can_fill_order(app, tx, w),
+        ContractInput::Refund { height } if app.tag == TOKEN => {
+            htlc::can_unlock_htlc(app, tx, w, Some(height))
+        },
+        ContractInput::Release { height } if app.tag == TOKEN => {
+            vesting::can_release_vested(app, tx, w, Some(height))
         },
-        TOKEN => {
-            check!(token_contract_satisfied(app, tx));
+        ContractInput::Stake { height } if app.tag == TOKEN => {
+            staking::can_stake(app, tx, w, height)
         },
-        _ => unreachable!(),
+        ContractInput::Unstake { height } => staking::can_unstake(&token_app, tx, w, Some(height)),
+        ContractInput::UpdateMetadata
+        | ContractInput::Swap
+        | ContractInput::Fill
+        | ContractInput::Refund { .. }
+        | ContractInput::Release { .. }
+        | ContractInput::Stake { .. } => Err(ContractError::UnsupportedOperation),
     }
-    true
+}
+
+/// Checks that an NFT with an airdrop, if spent and recreated, is recreated
+/// once, at the destination script it was spent from. A second copy would carry
+/// its own `claimed` bitmap, letting every allocation be claimed again.
+///
+/// The scripts are taken from `tx.coin_ins` and `tx.coin_outs`, which list the
+/// native inputs and outputs in the same order as `tx.ins` and `tx.outs`.
+fn check_airdrop_custody(nft_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let Some(input) = tx.ins.iter().position(|(_, charms)| {
+        charms
+            .get(nft_app)
+            .and_then(reserve_content)
+            .is_some_and(|content| content.airdrop.is_some())
+    }) else {
+        return Ok(());
+    };
+    let outputs: Vec<usize> = tx
+        .outs
+        .iter()
+        .enumerate()
+        .filter(|(_, charms)| charms.contains_key(nft_app))
+        .map(|(output, _)| output)
+        .collect();
+    let output = match outputs[..] {
+        [] => return Ok(()),
+        [output] => output,
+        _ => return Err(ContractError::DuplicateOutputNft(outputs.len())),
+    };
+    let spent_from = tx
+        .coin_ins
+        .as_ref()
+        .and_then(|coin_ins| coin_ins.get(input))
+        .ok_or(ContractError::AirdropNftMoved)?;
+    let sent_to = tx
+        .coin_outs
+        .as_ref()
+        .and_then(|coin_outs| coin_outs.get(output))
+        .ok_or(ContractError::MissingDestination(output))?;
+    if sent_to.dest != spent_from.dest {
+        return Err(ContractError::AirdropNftMoved);
+    }
+    Ok(())
 }
 
 /// Validates NFT contract satisfaction.
 ///
 /// Checks whether the transaction satisfies the NFT contract by verifying that
-/// either a new NFT can be minted or associated tokens can be minted.
+/// either a new NFT can be minted, the NFT is preserved or finalized, or
+/// associated tokens are minted or burned against its supply.
 ///
 /// # Arguments
 ///
 /// * `app` - The NFT application context
 /// * `tx` - The transaction to validate
-/// * `w` - Witness data containing the UTXO ID for NFT minting
+/// * `w` - Witness data containing the UTXO ID for NFT minting, or the
+///   [`MintWitness`] for token minting
 ///
 /// # Returns
 ///
-/// Returns `true` if either NFT or token minting conditions are satisfied.
+/// Returns `Ok(())` if NFT minting, preservation, or token supply conditions are satisfied.
 ///
-fn nft_contract_satisfied(app: &App, tx: &Transaction, w: &Data) -> bool {
-    let token_app = &App {
-        tag: TOKEN,
-        identity: app.identity.clone(),
-        vk: app.vk.clone(),
+fn nft_contract_satisfied(app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    // Without the NFT in the inputs this can only be a mint of a new NFT.
+    let Ok(input_content) = input_nft_content(app, tx) else {
+        return can_mint_nft(app, tx, w);
     };
-    // Allow: minting new NFT, minting tokens, OR preserving NFT (for transfers)
-    check!(can_mint_nft(app, tx, w) || can_mint_token(token_app, tx) || can_preserve_nft(app, tx));
-    true
+    // Spending the NFT without recreating it burns it.
+    if !has_output_nft(app, tx) {
+        return can_burn_nft(app, tx);
+    }
+    // Finalizing is a transition of its own.
+    if !input_content.finalized && output_nft_content(app, tx).is_ok_and(|c| c.finalized) {
+        return can_finalize_nft(app, tx, input_content);
+    }
+    // So is pausing or resuming token transfers.
+    if output_nft_content(app, tx).is_ok_and(|c| c.paused != input_content.paused) {
+        return can_toggle_pause(app, tx, input_content);
+    }
+    // So is updating the mutable metadata without touching the supply.
+    if output_nft_content(app, tx).is_ok_and(|c| {
+        c.remaining == input_content.remaining
+            && (c.uri != input_content.uri
+                || c.description != input_content.description
+                || c.metadata_locked != input_content.metadata_locked)
+    }) {
+        return can_update_metadata(app, tx);
+    }
+    // Otherwise allow: preserving the NFT (for transfers) OR minting/burning tokens
+    can_preserve_nft(app, tx).or_else(|_| can_change_supply(&token_app_for(app), tx, w))
 }
 
-/// Validates whether an NFT can be preserved (transferred without state change).
+/// Validates whether an NFT can be finalized, permanently renouncing minting.
 ///
-/// This allows the NFT to be moved between addresses while keeping its state unchanged.
-/// Used for pure token transfers where the NFT's remaining supply doesn't change.
-fn can_preserve_nft(nft_app: &App, tx: &Transaction) -> bool {
-    // Get NFT content from inputs
-    let Some(input_content): Option<NftContent> =
-        charm_values(nft_app, tx.ins.iter().map(|(_, v)| v)).find_map(|data| data.value().ok())
-    else {
-        return false;
+/// The output NFT must be the unfinalized `input_content` with only `finalized`
+/// set. From then on [`can_mint_token`] rejects every mint, so holders can verify
+/// on chain that the supply is fixed. Burns remain possible.
+fn can_finalize_nft(
+    nft_app: &App,
+    tx: &Transaction,
+    input_content: NftContent,
+) -> Result<(), ContractError> {
+    if input_content.airdrop.is_some() {
+        return Err(ContractError::AirdropNftLocked);
+    }
+    let output_content = output_nft_content(nft_app, tx)?;
+    let expected_content = NftContent {
+        finalized: true,
+        ..input_content
     };
+    if output_content != expected_content {
+        return Err(ContractError::NftStateChanged);
+    }
+    Ok(())
+}
 
-    // Get NFT content from outputs
-    let Some(output_content): Option<NftContent> =
-        charm_values(nft_app, tx.outs.iter()).find_map(|data| data.value().ok())
-    else {
-        return false;
+/// Validates whether the NFT holder can pause or resume token transfers.
+///
+/// The NFT must be `pausable`, as only its tokens present it on every transfer.
+/// The output NFT must be `input_content` with only `paused` flipped. Spending
+/// the NFT is the holder's authorization, as for every other NFT transition.
+fn can_toggle_pause(
+    nft_app: &App,
+    tx: &Transaction,
+    input_content: NftContent,
+) -> Result<(), ContractError> {
+    if !input_content.pausable {
+        return Err(ContractError::NotPausable);
+    }
+    if input_content.airdrop.is_some() {
+        return Err(ContractError::AirdropNftLocked);
+    }
+    let output_content = output_nft_content(nft_app, tx)?;
+    let expected_content = NftContent {
+        paused: !input_content.paused,
+        ..input_content
     };
+    if output_content != expected_content {
+        return Err(ContractError::NftStateChanged);
+    }
+    Ok(())
+}
+
+/// Validates whether an NFT can be burned, retiring its reserve.
+///
+/// Called for transactions spending the NFT without recreating it. Its
+/// `remaining` supply must be exhausted unless it is `burnable` and has no
+/// airdrop, whose allocations anyone could otherwise void. Tokens already
+/// minted stay valid and can still be transferred, but can no longer be minted
+/// or burned. A `pausable` NFT, or one with an `allowlist_root`, is never
+/// burned, as its tokens only move in transactions spending or referencing it.
+fn can_burn_nft(nft_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let input_content = input_nft_content(nft_app, tx)?;
+    if input_content.pausable || input_content.allowlist_root.is_some() {
+        return Err(ContractError::NftStillRequired);
+    }
+    if input_content.remaining > 0 && (!input_content.burnable || input_content.airdrop.is_some()) {
+        return Err(ContractError::NftNotBurnable {
+            remaining: input_content.remaining,
+        });
+    }
+    Ok(())
+}
 
-    // NFT is preserved if ticker and remaining supply are unchanged
-    input_content.ticker == output_content.ticker && input_content.remaining == output_content.remaining
+/// Validates whether the NFT holder can update the NFT metadata.
+///
+/// Only `uri` and `description` may change, within the [`check_metadata`]
+/// limits, and `metadata_locked` may be set to freeze them for good. The supply,
+/// ticker and every other field must be unchanged. The metadata of an NFT with
+/// an airdrop, which anyone can spend, cannot be updated.
+fn can_update_metadata(nft_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let input_content = input_nft_content(nft_app, tx)?;
+    let output_content = output_nft_content(nft_app, tx)?;
+    if input_content.airdrop.is_some() {
+        return Err(ContractError::AirdropNftLocked);
+    }
+    if input_content.metadata_locked {
+        return Err(ContractError::MetadataLocked);
+    }
+    let expected_content = NftContent {
+        uri: output_content.uri.clone(),
+        description: output_content.description.clone(),
+        metadata_locked: output_content.metadata_locked,
+        ..input_content
+    };
+    if output_content != expected_content {
+        return Err(ContractError::NftStateChanged);
+    }
+    check_metadata(&output_content)
+}
+
+/// Validates whether an NFT can be preserved (transferred without state change).
+///
+/// This allows the NFT to be moved between addresses while keeping its state unchanged.
+/// Used for pure token transfers where the NFT's remaining supply doesn't change.
+fn can_preserve_nft(nft_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let input_content = input_nft_content(nft_app, tx)?;
+    let output_content = output_nft_content(nft_app, tx)?;
+
+    // NFT is preserved if its whole state, including its metadata, is unchanged
+    if input_content != output_content {
+        return Err(ContractError::NftStateChanged);
+    }
+    Ok(())
 }
 
 /// Validates whether an NFT can be minted in the transaction.
 ///
 /// This function enforces the NFT minting rules:
 /// 1. The witness data must contain a valid UTXO ID string
-/// 2. The hash of the witness must match the NFT's identity
+/// 2. The hash of the witness must match the NFT's identity, or for a collection
+///    item its [`collection_identity`]
 /// 3. The transaction must spend the UTXO referenced in the witness
 /// 4. Exactly one NFT must be created in the outputs
-/// 5. The NFT must contain valid `NftContent` data
+/// 5. The NFT must contain valid `NftContent` data, and a collection item must be
+///    minted with every other item of its collection
+/// 6. If the NFT records a `max_supply`, `minted + remaining` must equal it
+/// 7. The token metadata must be within its limits
+/// 8. The ticker must follow the ticker grammar (see [`check_ticker`])
+/// 9. The mint authority and multisig, if any, must be valid keys with a reachable
+///    threshold
+/// 10. The emission schedule, if any, must have a halving interval and the NFT
+///     must track its `max_supply` and have no airdrop, whose claims are not
+///     paced by block height
 ///
 /// # Arguments
 ///
@@ -141,34 +1604,194 @@ fn can_preserve_nft(nft_app: &App, tx: &Transaction) -> bool {
 /// * `tx` - The transaction attempting to mint the NFT
 /// * `w` - Witness data containing the UTXO ID string
 ///
-/// # Returns
+/// # Errors
 ///
-/// Returns `true` if all NFT minting conditions are satisfied, `false` otherwise.
-pub fn can_mint_nft(nft_app: &App, tx: &Transaction, w: &Data) -> bool {
-    let w_str: Option<String> = w.value().ok();
-
-    check!(w_str.is_some());
-    let w_str = w_str.unwrap();
+/// Returns the [`ContractError`] for the first minting rule that is not satisfied.
+pub fn can_mint_nft(nft_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let w_str: String = w.value().map_err(|_| ContractError::WitnessNotString)?;
 
-    eprintln!("Minting NFT with witness UTXO: {}", w_str);
+    eprintln!("Minting NFT with witness UTXO: {w_str}");
     for (i, (utxo_id, _)) in tx.ins.iter().enumerate() {
-        eprintln!("Transaction Input #{}: {:?}", i, utxo_id);
+        eprintln!("Transaction Input #{i}: {utxo_id:?}");
     }
 
-    // can only mint an NFT with this contract if the hash of `w` is the identity of the NFT.
-    check!(hash(&w_str) == nft_app.identity);
+    // can only mint an NFT with this contract if the hash of `w` is the identity of the NFT,
+    // or the identity of its position in a collection.
+    let identity = charm_values(nft_app, tx.outs.iter())
+        .find_map(reserve_content)
+        .and_then(|content| content.collection)
+        .map_or_else(|| hash(&w_str), |item| collection_identity(&w_str, item.index));
+    if identity != nft_app.identity {
+        return Err(ContractError::IdentityMismatch);
+    }
 
     // can only mint an NFT with this contract if spending a UTXO with the same ID as passed in `w`.
-    let w_utxo_id = UtxoId::from_str(&w_str).unwrap();
-    check!(tx.ins.iter().any(|(utxo_id, _)| utxo_id == &w_utxo_id));
+    let w_utxo_id =
+        UtxoId::from_str(&w_str).map_err(|_| ContractError::InvalidWitnessUtxoId(w_str.clone()))?;
+    if !tx.ins.iter().any(|(utxo_id, _)| utxo_id == &w_utxo_id) {
+        return Err(ContractError::WitnessUtxoNotSpent);
+    }
 
     let nft_charms = charm_values(nft_app, tx.outs.iter()).collect::<Vec<_>>();
 
     // can mint exactly one NFT.
-    check!(nft_charms.len() == 1);
+    if nft_charms.len() != 1 {
+        return Err(ContractError::NftCountMismatch(nft_charms.len()));
+    }
     // the NFT has the correct structure.
-    check!(nft_charms[0].value::<NftContent>().is_ok());
-    true
+    let content = reserve_content(nft_charms[0]).ok_or(ContractError::InvalidNftContent)?;
+    // a collection item is minted together with the rest of its collection.
+    if let Some(item) = content.collection {
+        check_collection(nft_app, tx, &w_str, item)?;
+    }
+    // the NFT starts with consistent supply accounting.
+    check_supply_invariant(&content)?;
+    // the NFT carries a well-formed ticker and metadata; they are frozen from here on.
+    check_ticker(&content.ticker)?;
+    check_metadata(&content)?;
+    // the mint authorities can approve mints.
+    check_mint_authorities(&content)?;
+    // the emission schedule can bound the minted counter.
+    if let Some(schedule) = &content.emission {
+        if schedule.halving_interval == 0
+            || content.max_supply.is_none()
+            || content.airdrop.is_some()
+        {
+            return Err(ContractError::InvalidEmissionSchedule);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the collection of `item` is minted in full: exactly one NFT at
+/// each index below its size, each committing to its own position.
+fn check_collection(
+    nft_app: &App,
+    tx: &Transaction,
+    utxo_id: &str,
+    item: CollectionItem,
+) -> Result<(), ContractError> {
+    if item.index >= item.size {
+        return Err(ContractError::InvalidCollectionIndex {
+            index: item.index,
+            size: item.size,
+        });
+    }
+    let mut outputs: BTreeMap<&B32, Vec<&Data>> = BTreeMap::new();
+    for (app, data) in tx.outs.iter().flatten() {
+        if app.tag == NFT && app.vk == nft_app.vk {
+            outputs.entry(&app.identity).or_default().push(data);
+        }
+    }
+    for index in 0..item.size {
+        let member = match outputs
+            .get(&collection_identity(utxo_id, index))
+            .map(Vec::as_slice)
+        {
+            None | Some([]) => return Err(ContractError::MissingCollectionItem(index)),
+            Some([data]) => reserve_content(data),
+            Some(_) => return Err(ContractError::DuplicateCollectionItem(index)),
+        };
+        let expected = Some(CollectionItem {
+            index,
+            size: item.size,
+        });
+        if member.is_none_or(|content| content.collection != expected) {
+            return Err(ContractError::CollectionItemMismatch(index));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the mint authority and multisig keys are valid and the threshold reachable.
+fn check_mint_authorities(content: &NftContent) -> Result<(), ContractError> {
+    for authority in content
+        .mint_authority
+        .iter()
+        .chain(&content.mint_authorities)
+    {
+        VerifyingKey::from_bytes(&authority.0).map_err(|_| ContractError::InvalidMintAuthority)?;
+    }
+    let authorities = content.mint_authorities.len();
+    let threshold = content.mint_threshold;
+    let threshold_reachable = usize::try_from(threshold).is_ok_and(|t| t <= authorities);
+    if (authorities > 0 && threshold == 0) || !threshold_reachable {
+        return Err(ContractError::InvalidMintThreshold {
+            threshold,
+            authorities,
+        });
+    }
+    for (i, authority) in content.mint_authorities.iter().enumerate() {
+        if content.mint_authorities[..i].contains(authority) {
+            return Err(ContractError::DuplicateMintAuthority);
+        }
+    }
+    Ok(())
+}
+
+/// Checks a ticker against the ticker grammar.
+///
+/// A valid ticker has between [`MIN_TICKER_LEN`] and [`MAX_TICKER_LEN`] characters,
+/// each an uppercase ASCII letter, an ASCII digit or one of [`TICKER_PUNCTUATION`],
+/// e.g. `MY-TOKEN`.
+///
+/// # Errors
+///
+/// Returns [`ContractError::InvalidTickerLength`] or [`ContractError::InvalidTickerChar`]
+/// if the ticker does not follow the grammar.
+pub fn check_ticker(ticker: &str) -> Result<(), ContractError> {
+    let len = ticker.chars().count();
+    if !(MIN_TICKER_LEN..=MAX_TICKER_LEN).contains(&len) {
+        return Err(ContractError::InvalidTickerLength(len));
+    }
+    if let Some(c) = ticker
+        .chars()
+        .find(|c| !(c.is_ascii_uppercase() || c.is_ascii_digit() || TICKER_PUNCTUATION.contains(c)))
+    {
+        return Err(ContractError::InvalidTickerChar(c));
+    }
+    Ok(())
+}
+
+/// Checks the token metadata of an NFT against [`MAX_DECIMALS`], [`MAX_NAME_LEN`],
+/// [`MAX_URI_LEN`] and [`MAX_DESCRIPTION_LEN`].
+fn check_metadata(content: &NftContent) -> Result<(), ContractError> {
+    if let Some(decimals) = content.decimals.filter(|&decimals| decimals > MAX_DECIMALS) {
+        return Err(ContractError::TooManyDecimals(decimals));
+    }
+    if let Some(name) = content
+        .name
+        .as_ref()
+        .filter(|name| name.len() > MAX_NAME_LEN)
+    {
+        return Err(ContractError::NameTooLong(name.len()));
+    }
+    if let Some(uri) = content.uri.as_ref().filter(|uri| uri.len() > MAX_URI_LEN) {
+        return Err(ContractError::UriTooLong(uri.len()));
+    }
+    if let Some(description) = content
+        .description
+        .as_ref()
+        .filter(|description| description.len() > MAX_DESCRIPTION_LEN)
+    {
+        return Err(ContractError::DescriptionTooLong(description.len()));
+    }
+    Ok(())
+}
+
+/// Checks the `minted + remaining == max_supply` invariant of NFTs that track their supply.
+fn check_supply_invariant(content: &NftContent) -> Result<(), ContractError> {
+    let Some(max_supply) = content.max_supply else {
+        return Ok(());
+    };
+    if content.minted.checked_add(content.remaining) != Some(max_supply) {
+        return Err(ContractError::SupplyInvariantViolated {
+            max_supply,
+            minted: content.minted,
+            remaining: content.remaining,
+        });
+    }
+    Ok(())
 }
 
 /// Computes the SHA-256 hash of input data.
@@ -195,44 +1818,215 @@ pub fn hash(data: &str) -> B32 {
     B32(hash.into())
 }
 
+/// Derives the identity of the NFT at `index` of a collection minted by spending
+/// the UTXO `utxo_id`.
+///
+/// The identity is the SHA-256 hash of the UTXO ID string followed by the
+/// big-endian `index`, so every index yields a distinct NFT.
+pub fn collection_identity(utxo_id: &str, index: u32) -> B32 {
+    let hash = Sha256::new()
+        .chain_update(utxo_id)
+        .chain_update(index.to_be_bytes())
+        .finalize();
+    B32(hash.into())
+}
+
+/// Hashes an airdrop allocation into its Merkle leaf.
+///
+/// The leaf is the [`leaf_hash`] of the big-endian `index` and `amount` followed
+/// by the destination script `dest`.
+pub fn airdrop_leaf(index: u32, dest: &[u8], amount: u64) -> B32 {
+    let data = [&index.to_be_bytes()[..], &amount.to_be_bytes(), dest].concat();
+    leaf_hash(&data)
+}
+
 /// Validates token contract satisfaction.
 ///
 /// Checks whether the transaction satisfies the token contract by verifying
-/// that tokens can be minted according to the rules enforced by the managing NFT.
+/// that tokens are swapped or transferred, or minted or burned according to the
+/// rules enforced by the managing NFT.
 ///
 /// # Arguments
 ///
 /// * `token_app` - The token application context
 /// * `tx` - The transaction to validate
+/// * `w` - Witness data containing the [`SwapOrder`] for a swap, or the
+///   [`MintWitness`] for token minting
 ///
 /// # Returns
 ///
-/// Returns `true` if token minting conditions are satisfied.
+/// Returns `Ok(())` if token transfer, minting or burning conditions are satisfied.
 ///
-fn token_contract_satisfied(token_app: &App, tx: &Transaction) -> bool {
-    // Allow: pure transfer (balanced tokens) OR minting new tokens
-    check!(can_transfer_token(token_app, tx) || can_mint_token(token_app, tx));
-    true
+fn token_contract_satisfied(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+) -> Result<(), ContractError> {
+    // A witness committing a swap order makes this a swap.
+    if w.value::<SwapOrder>().is_ok() {
+        return swap::can_swap_token(token_app, tx, w);
+    }
+    // Allow: pure transfer (balanced tokens) OR minting/burning tokens
+    can_transfer_token(token_app, tx, w).or_else(|err| {
+        // Changing the supply is only possible when the managing NFT is spent.
+        if input_nft_content(&nft_app_for(token_app), tx).is_ok() {
+            can_change_supply(token_app, tx, w)
+        } else {
+            Err(err)
+        }
+    })
+}
+
+/// Validates a change in token supply against the managing NFT.
+///
+/// Transactions that destroy tokens without taking from the NFT remaining supply
+/// are checked as burns. All others are checked as mints, or as claims if the
+/// NFT has an airdrop.
+fn can_change_supply(token_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let nft_app = nft_app_for(token_app);
+    let input_content = input_nft_content(&nft_app, tx)?;
+    let output_content = output_nft_content(&nft_app, tx)?;
+    let incoming_supply = input_content.remaining;
+    let outgoing_supply = output_content.remaining;
+    let (input_token_amount, output_token_amount) = token_amounts(token_app, tx)?;
+    if outgoing_supply >= incoming_supply && output_token_amount < input_token_amount {
+        can_burn_token(token_app, tx)
+    } else if input_content.airdrop.is_some() {
+        can_claim_airdrop(token_app, tx, w)
+    } else {
+        can_mint_token(token_app, tx, w, None)
+    }
 }
 
 /// Validates whether tokens can be transferred (pure transfer, no minting).
 ///
 /// A pure transfer requires total input token amount to equal total output token amount.
-fn can_transfer_token(token_app: &App, tx: &Transaction) -> bool {
-    let Some(input_amount) = sum_token_amount(token_app, tx.ins.iter().map(|(_, v)| v)).ok() else {
-        return false;
-    };
-    let Some(output_amount) = sum_token_amount(token_app, tx.outs.iter()).ok() else {
-        return false;
-    };
+///
+/// While the NFT is `paused`, only transfers that spend the NFT are approved.
+/// A reference to the NFT may be stale, so only a spent NFT shows its current
+/// state: pausable tokens stay pausable and only move in transactions spending
+/// the NFT, which the holder stops approving while it is `paused`. For tokens of
+/// other NFTs the pause is advisory, checked only when the NFT is referenced.
+///
+/// Allowlisted tokens stay allowlisted, and every output receiving them needs a
+/// proof in the [`TransferWitness`] that its destination is in the allowlist of
+/// the NFT, which the transaction must spend or reference.
+fn can_transfer_token(token_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let nft_app = nft_app_for(token_app);
+    if input_nft_content(&nft_app, tx).is_err()
+        && charm_values(&nft_app, tx.refs.iter().map(|(_, v)| v))
+            .filter_map(reserve_content)
+            .any(|content| content.paused)
+    {
+        return Err(ContractError::TransfersPaused);
+    }
+    let (input, output) = token_amounts(token_app, tx)?;
     // Pure transfer: input equals output (no minting), and must have tokens
-    input_amount == output_amount && input_amount > 0
+    if input != output {
+        return Err(ContractError::UnbalancedTransfer { input, output });
+    }
+    if input == 0 {
+        return Err(ContractError::EmptyTransfer);
+    }
+    check_transfer_pausable(token_app, tx)?;
+    check_transfer_allowlist(token_app, tx, w)
+}
+
+/// Checks that pausable tokens are transferred in the pausable form, in a
+/// transaction spending the NFT.
+fn check_transfer_pausable(token_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let mut pausable_inputs = false;
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        pausable_inputs |= matches!(token_amount(data)?, TokenAmount::Pausable { .. });
+    }
+    for data in tx.outs.iter().filter_map(|charms| charms.get(token_app)) {
+        if matches!(token_amount(data)?, TokenAmount::Pausable { .. }) != pausable_inputs {
+            return Err(ContractError::TokenFormMismatch);
+        }
+    }
+    if !pausable_inputs {
+        return Ok(());
+    }
+
+    if input_nft_content(&nft_app_for(token_app), tx).is_err() {
+        return Err(ContractError::PauseUnavailable);
+    }
+    Ok(())
+}
+
+/// Checks that allowlisted tokens are transferred to allowlisted destinations only.
+fn check_transfer_allowlist(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+) -> Result<(), ContractError> {
+    let mut allowlisted_inputs = false;
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        allowlisted_inputs |= matches!(token_amount(data)?, TokenAmount::Allowlisted { .. });
+    }
+    for data in tx.outs.iter().filter_map(|charms| charms.get(token_app)) {
+        if matches!(token_amount(data)?, TokenAmount::Allowlisted { .. }) != allowlisted_inputs {
+            return Err(ContractError::TokenFormMismatch);
+        }
+    }
+    if !allowlisted_inputs {
+        return Ok(());
+    }
+
+    let nft_app = nft_app_for(token_app);
+    let root = charm_values(&nft_app, tx.ins.iter().chain(&tx.refs).map(|(_, v)| v))
+        .filter_map(reserve_content)
+        .find_map(|content| content.allowlist_root)
+        .ok_or(ContractError::AllowlistUnavailable)?;
+    let witness: TransferWitness = w.value().unwrap_or_default();
+    check_allowlist(&root, token_app, tx, &witness.allowlist_proofs)
+}
+
+/// Checks that the destination script of every output holding `token_app` tokens
+/// is proven to be in the allowlist with `root`.
+///
+/// The destination scripts are taken from `tx.coin_outs`, which lists the native
+/// outputs of the transaction in the same order as `tx.outs`.
+fn check_allowlist(
+    root: &B32,
+    token_app: &App,
+    tx: &Transaction,
+    proofs: &BTreeMap<usize, Vec<B32>>,
+) -> Result<(), ContractError> {
+    for (output, _) in tx
+        .outs
+        .iter()
+        .enumerate()
+        .filter(|(_, charms)| charms.contains_key(token_app))
+    {
+        let destination = tx
+            .coin_outs
+            .as_ref()
+            .and_then(|coin_outs| coin_outs.get(output))
+            .ok_or(ContractError::MissingDestination(output))?;
+        let proof = proofs
+            .get(&output)
+            .ok_or(ContractError::MissingAllowlistProof(output))?;
+        if !verify_proof(root, &leaf_hash(&destination.dest), proof) {
+            return Err(ContractError::NotAllowlisted(output));
+        }
+    }
+    Ok(())
 }
 
 /// Validates whether tokens can be minted in the transaction.
 ///
 /// This function enforces supply-controlled token minting by:
-/// 1. Reading the NFT's remaining supply from transaction inputs
+/// 1. Reading the NFT's remaining supply from transaction inputs, unless the
+///    NFT is finalized
 /// 2. Reading the NFT's remaining supply from transaction outputs
 /// 3. Ensuring the supply only decreases (incoming >= outgoing)
 /// 4. Calculating the difference in token amounts between inputs and outputs
@@ -246,56 +2040,550 @@ fn can_transfer_token(token_app: &App, tx: &Transaction) -> bool {
 ///
 /// * `token_app` - The token application context
 /// * `tx` - The transaction attempting to mint tokens
+/// * `w` - Witness data containing the [`MintWitness`] if the NFT has a mint authority
+/// * `height` - Block height declared in the public input, if any
 ///
-/// # Returns
+/// # Errors
 ///
-/// Returns `true` if tokens can be minted according to NFT supply constraints,
-/// `false` otherwise.
+/// Returns the [`ContractError`] for the first supply rule that is not satisfied.
 ///
 /// # Validation Rules
 ///
 /// - The managing NFT must be present in both inputs and outputs
+/// - The managing NFT must not be finalized
+/// - The managing NFT must not have an `airdrop`, whose supply only claims mint
 /// - NFT remaining supply must not increase (incoming >= outgoing)
+/// - Output tokens must not be fewer than input tokens (output >= input)
 /// - Tokens minted must equal the decrease in NFT supply:
 ///   `(output_tokens - input_tokens) == (incoming_supply - outgoing_supply)`
-fn can_mint_token(token_app: &App, tx: &Transaction) -> bool {
-    let nft_app = App {
-        tag: NFT,
-        identity: token_app.identity.clone(),
-        vk: token_app.vk.clone(),
-    };
+/// - If the NFT has a `max_mint_per_tx`, the supply decrease must not exceed it
+/// - If the NFT tracks its `max_supply`, `minted` must grow by the minted amount
+/// - If the NFT has an `emission` schedule, the new `minted` must not exceed what
+///   the schedule has released at `height`
+/// - All other NFT state must be unchanged
+/// - If the NFT has a `mint_authority`, a non-zero mint must be signed by it
+/// - If the NFT has `mint_authorities`, a non-zero mint must be signed by at
+///   least `mint_threshold` distinct members
+///
+/// All arithmetic is checked, so the rules hold identically in debug builds and
+/// in the release Wasm build where integer overflow would otherwise wrap.
+///
+/// Charms transactions do not expose the block height, so [`validate`] only
+/// accepts a `height` proven by a timelocked input (see [`HeightProof`]). Bitcoin
+/// mines the transaction above that height, and the allowance only grows with
+/// the height, so the schedule holds however late the mint confirms.
+fn can_mint_token(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+    height: Option<u64>,
+) -> Result<(), ContractError> {
+    let nft_app = nft_app_for(token_app);
 
-    let Some(nft_content): Option<NftContent> =
-        charm_values(&nft_app, tx.ins.iter().map(|(_, v)| v)).find_map(|data| data.value().ok())
-    else {
-        eprintln!("could not determine incoming remaining supply");
-        return false;
+    let input_content = input_nft_content(&nft_app, tx)?;
+    if input_content.finalized {
+        return Err(ContractError::SupplyFinalized);
+    }
+    // the supply of an NFT with an airdrop is reserved for the claims.
+    if input_content.airdrop.is_some() {
+        return Err(ContractError::AirdropOnly);
+    }
+    let output_content = output_nft_content(&nft_app, tx)?;
+    let minted = minted_token_amount(token_app, tx)?;
+
+    // can mint no more than what's allowed by the managing NFT state change.
+    let expected_content = minted_from_reserve(&input_content, &output_content, minted)?;
+
+    // minting only moves supply out of the reserve; nothing else may change.
+    if output_content.minted != expected_content.minted {
+        return Err(ContractError::MintedCounterMismatch {
+            expected: expected_content.minted,
+            actual: output_content.minted,
+        });
+    }
+    if output_content != expected_content {
+        return Err(ContractError::NftStateChanged);
+    }
+
+    // can mint no more than the emission schedule has released so far.
+    if let Some(schedule) = expected_content.emission.as_ref().filter(|_| minted > 0) {
+        let allowed = schedule.allowance_at(height.ok_or(ContractError::MissingBlockHeight)?);
+        if expected_content.minted > allowed {
+            return Err(ContractError::EmissionExceeded {
+                minted: expected_content.minted,
+                allowed,
+            });
+        }
+    }
+
+    // minted tokens take the form the NFT requires, and only go to allowlisted
+    // destinations if it has an allowlist.
+    check_token_forms(token_app, tx, &expected_content)?;
+    if let Some(root) = &expected_content.allowlist_root {
+        let witness: MintWitness = w.value().unwrap_or_default();
+        check_allowlist(root, token_app, tx, &witness.allowlist_proofs)?;
+    }
+
+    // the mint authorities, if any, approved exactly these token outputs.
+    if minted > 0 {
+        check_mint_authorization(&expected_content, token_app, tx, w)?;
+    }
+    Ok(())
+}
+
+/// Validates a claim of airdrop allocations against the reserve NFT.
+///
+/// Every claim in the [`ClaimWitness`] must prove that its allocation is in the
+/// airdrop, be unclaimed, and pay exactly its amount to its output, whose
+/// destination script must be the committed one. The NFT must be recreated with
+/// the claims marked and their total moved out of `remaining`, and nothing else
+/// may be minted. No mint authorization is needed: the issuer approved every
+/// allocation by committing the root.
+fn can_claim_airdrop(token_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let nft_app = nft_app_for(token_app);
+    let input_content = input_nft_content(&nft_app, tx)?;
+    if input_content.finalized {
+        return Err(ContractError::SupplyFinalized);
+    }
+    let mut airdrop = input_content
+        .airdrop
+        .clone()
+        .ok_or(ContractError::MissingAirdrop)?;
+    let witness: ClaimWitness = w.value().map_err(|_| ContractError::MissingClaimWitness)?;
+
+    // every allocation is in the airdrop, unclaimed, and paid to its destination.
+    let mut claimed: u64 = 0;
+    let mut outputs = BTreeSet::new();
+    for claim in &witness.claims {
+        if airdrop.is_claimed(claim.index) {
+            return Err(ContractError::AlreadyClaimed(claim.index));
+        }
+        let paid = match tx
+            .outs
+            .get(claim.output)
+            .and_then(|charms| charms.get(token_app))
+        {
+            Some(data) => token_amount(data)?.amount(),
+            None => 0,
+        };
+        if paid != claim.amount || !outputs.insert(claim.output) {
+            return Err(ContractError::InvalidClaim(claim.index));
+        }
+        let destination = tx
+            .coin_outs
+            .as_ref()
+            .and_then(|coin_outs| coin_outs.get(claim.output))
+            .ok_or(ContractError::MissingDestination(claim.output))?;
+        let leaf = airdrop_leaf(claim.index, &destination.dest, claim.amount);
+        if !verify_proof(&airdrop.root, &leaf, &claim.proof) {
+            return Err(ContractError::InvalidClaim(claim.index));
+        }
+        airdrop.set_claimed(claim.index);
+        claimed = claimed
+            .checked_add(claim.amount)
+            .ok_or(ContractError::TokenAmountOverflow)?;
+    }
+
+    // the claimed total, and only that, moves from the reserve into tokens.
+    let output_content = output_nft_content(&nft_app, tx)?;
+    let minted = minted_token_amount(token_app, tx)?;
+    if minted != claimed {
+        return Err(ContractError::ClaimAmountMismatch { minted, claimed });
+    }
+    let expected_content = NftContent {
+        airdrop: Some(airdrop),
+        ..minted_from_reserve(&input_content, &output_content, minted)?
     };
-    let incoming_supply = nft_content.remaining;
+    if output_content != expected_content {
+        return Err(ContractError::NftStateChanged);
+    }
 
-    let Some(nft_content): Option<NftContent> =
-        charm_values(&nft_app, tx.outs.iter()).find_map(|data| data.value().ok())
-    else {
-        eprintln!("could not determine outgoing remaining supply");
-        return false;
+    check_token_forms(token_app, tx, &expected_content)?;
+    if let Some(root) = &expected_content.allowlist_root {
+        check_allowlist(root, token_app, tx, &witness.allowlist_proofs)?;
+    }
+    Ok(())
+}
+
+/// Returns the number of `token_app` tokens the transaction creates.
+fn minted_token_amount(token_app: &App, tx: &Transaction) -> Result<u64, ContractError> {
+    let (input_token_amount, output_token_amount) = token_amounts(token_app, tx)?;
+    output_token_amount
+        .checked_sub(input_token_amount)
+        .ok_or(ContractError::MintUnderflow {
+            input: input_token_amount,
+            output: output_token_amount,
+        })
+}
+
+/// Checks that the NFT state change moves exactly `minted` tokens out of the
+/// reserve, and returns the NFT content the mint must leave behind.
+///
+/// The supply decrease is the drop in `remaining`, so no more than the reserve
+/// can ever be minted, and no more than `max_mint_per_tx` at once, whichever
+/// path takes it out of the reserve. If the NFT tracks its `max_supply`, `minted`
+/// grows by the same amount; nothing else changes.
+fn minted_from_reserve(
+    input_content: &NftContent,
+    output_content: &NftContent,
+    minted: u64,
+) -> Result<NftContent, ContractError> {
+    let incoming_supply = input_content.remaining;
+    let outgoing_supply = output_content.remaining;
+    let supply_decrease =
+        incoming_supply
+            .checked_sub(outgoing_supply)
+            .ok_or(ContractError::SupplyIncreased {
+                incoming: incoming_supply,
+                outgoing: outgoing_supply,
+            })?;
+    if minted != supply_decrease {
+        return Err(ContractError::MintAmountMismatch {
+            minted,
+            supply_decrease,
+        });
+    }
+    // the per-transaction cap limits what a leaked key can drain.
+    if let Some(cap) = input_content.max_mint_per_tx.filter(|&cap| minted > cap) {
+        return Err(ContractError::MintCapExceeded { minted, cap });
+    }
+    let expected_minted = if input_content.max_supply.is_some() {
+        input_content
+            .minted
+            .checked_add(minted)
+            .ok_or(ContractError::SupplyOverflow)?
+    } else {
+        input_content.minted
     };
-    let outgoing_supply = nft_content.remaining;
+    Ok(NftContent {
+        remaining: outgoing_supply,
+        minted: expected_minted,
+        ..input_content.clone()
+    })
+}
+
+/// Computes the message a mint authority signs to approve a token mint.
+///
+/// The message is the SHA-256 hash of [`MINT_MESSAGE_TAG`], the token identity,
+/// the UTXO holding the managing NFT, and for every output in order its token
+/// amount (zero for outputs without tokens) as a little-endian `u64`, followed
+/// by the length of its destination script as a little-endian `u64` and the
+/// script itself. The destination scripts are taken from `tx.coin_outs`, so the
+/// tokens cannot be redirected without a new signature. Since the NFT UTXO can
+/// only be spent once, a signature cannot be replayed.
+///
+/// # Arguments
+///
+/// * `token_app` - The token application context
+/// * `tx` - The transaction minting tokens
+///
+/// # Errors
+///
+/// Returns [`ContractError::MissingInputNft`] if the managing NFT is not spent,
+/// [`ContractError::MissingDestination`] if an output has no destination script,
+/// or [`ContractError::InvalidTokenAmount`] if a token amount is malformed.
+pub fn mint_message(token_app: &App, tx: &Transaction) -> Result<B32, ContractError> {
+    let nft_app = nft_app_for(token_app);
+    let (nft_utxo_id, _) = tx
+        .ins
+        .iter()
+        .find(|(_, charms)| charms.contains_key(&nft_app))
+        .ok_or(ContractError::MissingInputNft)?;
 
-    if incoming_supply < outgoing_supply {
-        eprintln!("incoming remaining supply must be >= outgoing remaining supply");
-        return false;
+    let mut hasher = Sha256::new();
+    hasher.update(MINT_MESSAGE_TAG);
+    hasher.update(token_app.identity.0);
+    hasher.update(nft_utxo_id.to_bytes());
+    for (output, charms) in tx.outs.iter().enumerate() {
+        let amount = match charms.get(token_app) {
+            Some(data) => token_amount(data)?.amount(),
+            None => 0,
+        };
+        let destination = tx
+            .coin_outs
+            .as_ref()
+            .and_then(|coin_outs| coin_outs.get(output))
+            .ok_or(ContractError::MissingDestination(output))?;
+        hasher.update(amount.to_le_bytes());
+        hasher.update((destination.dest.len() as u64).to_le_bytes());
+        hasher.update(&destination.dest);
+    }
+    Ok(B32(hasher.finalize().into()))
+}
+
+/// Checks that the [`MintWitness`] in `w` satisfies the mint authorities of `content`.
+fn check_mint_authorization(
+    content: &NftContent,
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+) -> Result<(), ContractError> {
+    if content.mint_authority.is_none() && content.mint_authorities.is_empty() {
+        return Ok(());
+    }
+    let witness: MintWitness = w.value().map_err(|_| ContractError::MissingMintSignature)?;
+    let message = mint_message(token_app, tx)?;
+    if let Some(authority) = &content.mint_authority {
+        verify_signature(authority, &message, &witness.signature)?;
+    }
+    if !content.mint_authorities.is_empty() {
+        check_multisig(content, &message, &witness.signatures)?;
     }
+    Ok(())
+}
+
+/// Checks that at least `mint_threshold` distinct `mint_authorities` signed `message`.
+///
+/// Every supplied signature must be valid and by a distinct mint authority.
+fn check_multisig(
+    content: &NftContent,
+    message: &B32,
+    signatures: &[MultisigSignature],
+) -> Result<(), ContractError> {
+    for (i, multisig) in signatures.iter().enumerate() {
+        if !content.mint_authorities.contains(&multisig.signer) {
+            return Err(ContractError::UnknownSigner);
+        }
+        if signatures[..i]
+            .iter()
+            .any(|other| other.signer == multisig.signer)
+        {
+            return Err(ContractError::DuplicateSigner);
+        }
+        verify_signature(&multisig.signer, message, &multisig.signature)?;
+    }
+    if usize::try_from(content.mint_threshold).map_or(true, |t| signatures.len() < t) {
+        return Err(ContractError::ThresholdNotMet {
+            threshold: content.mint_threshold,
+            signers: signatures.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Verifies a hex-encoded BIP-340 Schnorr `signature` by `key` over `message`.
+fn verify_signature(key: &B32, message: &B32, signature: &str) -> Result<(), ContractError> {
+    let key = VerifyingKey::from_bytes(&key.0).map_err(|_| ContractError::InvalidMintAuthority)?;
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+        .ok_or(ContractError::InvalidSignatureEncoding)?;
+    key.verify_raw(&message.0, &signature)
+        .map_err(|_| ContractError::InvalidMintSignature)
+}
+
+/// Validates a transaction spending soulbound tokens.
+///
+/// Soulbound tokens cannot be transferred, so the transaction must not output
+/// any tokens of `token_app`: every token spent is burned. The holder does not
+/// need the NFT for this; when the NFT is spent as well, its own contract checks
+/// the burn accounting, otherwise its `burned` counter is left untouched.
+fn can_burn_soulbound(token_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    if tx.outs.iter().any(|charms| charms.contains_key(token_app)) {
+        return Err(ContractError::NonTransferable);
+    }
+    Ok(())
+}
+
+/// Validates whether tokens can be burned in the transaction.
+///
+/// Burning destroys tokens, i.e. the outputs hold fewer tokens than the inputs.
+/// The managing NFT must be spent and recreated so that the burn is accounted for
+/// in its state, depending on its `recycle_burns` flag:
+///
+/// - `recycle_burns == false`: the burned amount is added to `burned` and
+///   `remaining` is unchanged, permanently retiring the tokens
+/// - `recycle_burns == true`: the burned amount is added back to `remaining`
+///   and `burned` is unchanged, so the tokens can be minted again; if the NFT
+///   tracks its `max_supply`, the amount is also taken off `minted`
+///
+/// Either way the circulating supply plus `remaining` plus `burned` stays constant.
+///
+/// # Arguments
+///
+/// * `token_app` - The token application context
+/// * `tx` - The transaction attempting to burn tokens
+///
+/// # Errors
+///
+/// Returns the [`ContractError`] for the first burn rule that is not satisfied.
+fn can_burn_token(token_app: &App, tx: &Transaction) -> Result<(), ContractError> {
+    let nft_app = nft_app_for(token_app);
+
+    let input_content = input_nft_content(&nft_app, tx)?;
+    let output_content = output_nft_content(&nft_app, tx)?;
 
-    let Some(input_token_amount) = sum_token_amount(token_app, tx.ins.iter().map(|(_, v)| v)).ok()
+    let (input_token_amount, output_token_amount) = token_amounts(token_app, tx)?;
+    let Some(burned) = input_token_amount
+        .checked_sub(output_token_amount)
+        .filter(|&burned| burned > 0)
     else {
-        eprintln!("could not determine input total token amount");
-        return false;
+        return Err(ContractError::UnbalancedTransfer {
+            input: input_token_amount,
+            output: output_token_amount,
+        });
     };
-    let Some(output_token_amount) = sum_token_amount(token_app, tx.outs.iter()).ok() else {
-        eprintln!("could not determine output total token amount");
-        return false;
+
+    let expected_content = if input_content.recycle_burns {
+        NftContent {
+            remaining: input_content
+                .remaining
+                .checked_add(burned)
+                .ok_or(ContractError::SupplyOverflow)?,
+            minted: if input_content.max_supply.is_some() {
+                input_content
+                    .minted
+                    .checked_sub(burned)
+                    .ok_or(ContractError::SupplyOverflow)?
+            } else {
+                input_content.minted
+            },
+            ..input_content
+        }
+    } else {
+        NftContent {
+            burned: input_content
+                .burned
+                .checked_add(burned)
+                .ok_or(ContractError::SupplyOverflow)?,
+            ..input_content
+        }
     };
 
-    // can mint no more than what's allowed by the managing NFT state change.
-    output_token_amount - input_token_amount == incoming_supply - outgoing_supply
+    if output_content.remaining != expected_content.remaining {
+        return Err(ContractError::BurnRemainingMismatch {
+            expected: expected_content.remaining,
+            actual: output_content.remaining,
+        });
+    }
+    if output_content.burned != expected_content.burned {
+        return Err(ContractError::BurnedCounterMismatch {
+            expected: expected_content.burned,
+            actual: output_content.burned,
+        });
+    }
+    if output_content.minted != expected_content.minted {
+        return Err(ContractError::MintedCounterMismatch {
+            expected: expected_content.minted,
+            actual: output_content.minted,
+        });
+    }
+    if output_content != expected_content {
+        return Err(ContractError::NftStateChanged);
+    }
+    // the tokens left keep the form the NFT requires.
+    check_token_forms(token_app, tx, &expected_content)
+}
+
+/// Returns the NFT app that manages the supply of `token_app`.
+fn nft_app_for(token_app: &App) -> App {
+    App {
+        tag: NFT,
+        identity: token_app.identity.clone(),
+        vk: token_app.vk.clone(),
+    }
+}
+
+/// Returns the token app whose supply is managed by `nft_app`.
+fn token_app_for(nft_app: &App) -> App {
+    App {
+        tag: TOKEN,
+        identity: nft_app.identity.clone(),
+        vk: nft_app.vk.clone(),
+    }
+}
+
+/// Returns whether the transaction outputs the NFT of `nft_app`.
+fn has_output_nft(nft_app: &App, tx: &Transaction) -> bool {
+    tx.outs.iter().any(|charms| charms.contains_key(nft_app))
+}
+
+/// Reads the content of a reserve NFT from `data`.
+///
+/// Collection parents and children, and content mixing their fields with those
+/// of a reserve, are not reserves: no token is ever minted under them.
+fn reserve_content(data: &Data) -> Option<NftContent> {
+    match data.value().ok()? {
+        NftState::Reserve(content) => Some(*content),
+        NftState::Collection(_) | NftState::Child(_) => None,
+    }
+}
+
+/// Reads the first valid [`NftContent`] of `nft_app` from the transaction inputs.
+fn input_nft_content(nft_app: &App, tx: &Transaction) -> Result<NftContent, ContractError> {
+    charm_values(nft_app, tx.ins.iter().map(|(_, v)| v))
+        .find_map(reserve_content)
+        .ok_or(ContractError::MissingInputNft)
+}
+
+/// Reads the [`NftContent`] of `nft_app` from the transaction outputs.
+///
+/// The NFT must be output exactly once: a second copy would carry a state of
+/// its own, such as an unfinalized reserve next to a finalized one.
+fn output_nft_content(nft_app: &App, tx: &Transaction) -> Result<NftContent, ContractError> {
+    let copies = charm_values(nft_app, tx.outs.iter()).count();
+    if copies > 1 {
+        return Err(ContractError::DuplicateOutputNft(copies));
+    }
+    charm_values(nft_app, tx.outs.iter())
+        .find_map(reserve_content)
+        .ok_or(ContractError::MissingOutputNft)
+}
+
+/// Sums the `token_app` amounts in the transaction as `(inputs, outputs)`.
+fn token_amounts(token_app: &App, tx: &Transaction) -> Result<(u64, u64), ContractError> {
+    let input = checked_token_sum(token_app, tx.ins.iter().map(|(_, v)| v))?;
+    let output = checked_token_sum(token_app, tx.outs.iter())?;
+    Ok((input, output))
+}
+
+/// Sums the `token_app` amounts in `strings_of_charms`, rejecting overflow.
+///
+/// Unlike `charms_sdk::data::sum_token_amount`, this never wraps around in release builds.
+fn checked_token_sum<'a>(
+    token_app: &App,
+    strings_of_charms: impl Iterator<Item = &'a Charms>,
+) -> Result<u64, ContractError> {
+    strings_of_charms
+        .filter_map(|charms| charms.get(token_app))
+        .try_fold(0u64, |total, data| {
+            total
+                .checked_add(token_amount(data)?.amount())
+                .ok_or(ContractError::TokenAmountOverflow)
+        })
+}
+
+/// Reads the [`TokenAmount`] held by a token charm.
+fn token_amount(data: &Data) -> Result<TokenAmount, ContractError> {
+    data.value().map_err(|_| ContractError::InvalidTokenAmount)
+}
+
+/// Returns whether the transaction spends soulbound tokens of `token_app`.
+fn has_soulbound_input(token_app: &App, tx: &Transaction) -> Result<bool, ContractError> {
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if token_amount(data)?.is_soulbound() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Checks that every `token_app` output is in the form [`TokenAmount::minted_by`]
+/// the NFT `content`.
+fn check_token_forms(
+    token_app: &App,
+    tx: &Transaction,
+    content: &NftContent,
+) -> Result<(), ContractError> {
+    for data in tx.outs.iter().filter_map(|charms| charms.get(token_app)) {
+        let amount = token_amount(data)?;
+        if amount != TokenAmount::minted_by(content, amount.amount()) {
+            return Err(ContractError::TokenFormMismatch);
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,81 @@
+//! SHA-256 Merkle trees, used for allowlists of destination scripts.
+//!
+//! Leaves and inner nodes are hashed with distinct prefixes so an inner node can
+//! never pass for a leaf. The two children of a node are hashed in sorted order,
+//! so a proof is just the list of sibling hashes from the leaf up to the root.
+//! A node without a sibling is promoted to the next level unchanged.
+
+use charms_sdk::data::B32;
+use sha2::{Digest, Sha256};
+
+/// Prefix of every leaf hash.
+const LEAF_PREFIX: u8 = 0;
+
+/// Prefix of every inner node hash.
+const NODE_PREFIX: u8 = 1;
+
+/// Hashes `data` into a Merkle leaf.
+pub fn leaf_hash(data: &[u8]) -> B32 {
+    let hash = Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(data)
+        .finalize();
+    B32(hash.into())
+}
+
+/// Hashes two sibling nodes into their parent, in sorted order.
+pub fn node_hash(a: &B32, b: &B32) -> B32 {
+    let (left, right) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+    let hash = Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left.0)
+        .chain_update(right.0)
+        .finalize();
+    B32(hash.into())
+}
+
+/// Computes the root of the tree over `leaves`, or `None` if there are none.
+pub fn merkle_root(leaves: &[B32]) -> Option<B32> {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.pop()
+}
+
+/// Computes the proof of the leaf at `index`, or `None` if it is out of range.
+pub fn merkle_proof(leaves: &[B32], mut index: usize) -> Option<Vec<B32>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(sibling.clone());
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Returns whether `proof` shows that `leaf` is in the tree with `root`.
+pub fn verify_proof(root: &B32, leaf: &B32, proof: &[B32]) -> bool {
+    proof
+        .iter()
+        .fold(leaf.clone(), |node, sibling| node_hash(&node, sibling))
+        == *root
+}
+
+/// Hashes each pair of nodes of a level into the level above.
+fn next_level(level: &[B32]) -> Vec<B32> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [node] => node.clone(),
+            _ => unreachable!("chunks of two"),
+        })
+        .collect()
+}
@@ -0,0 +1,142 @@
+//! Order book lite: maker-signed limit orders that takers fill partially.
+//!
+//! A maker places an order by moving tokens into the offered form, whose
+//! [`LimitOrder`] sells them at `price` sats per token. The order output must
+//! sit at a script takers can spend. A taker fills any part of the order by
+//! paying the maker's destination script for it, and the rest of the order is
+//! carried into a new output in the offered form, under the same terms. Plain
+//! tokens returned to the maker's destination script are not filled and need no
+//! payment, which is how the maker cancels the order.
+//!
+//! Orders do not expire: a transaction can only prove it confirms above a block
+//! height (see [`HeightProof`](crate::HeightProof)), never below one, so an
+//! expiry could not be enforced.
+
+use charms_sdk::data::{App, Data, Transaction, B32};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{can_transfer_token, token_amount, verify_signature, ContractError, TokenAmount};
+
+/// Domain separation tag prefixed to every [`order_message`].
+const ORDER_MESSAGE_TAG: &[u8] = b"my-token/order/v2";
+
+/// Terms of a limit order, signed by the maker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitOrder {
+    /// Hex-encoded 32-byte x-only public key of the maker
+    pub maker: String,
+    /// Destination script of the maker, receiving the payments
+    pub maker_dest: Vec<u8>,
+    /// Price in sats per token
+    pub price: u64,
+    /// Hex-encoded 64-byte BIP-340 Schnorr signature over [`order_message`]
+    pub signature: String,
+}
+
+/// Computes the message a maker signs to place a limit order.
+///
+/// The message is the SHA-256 hash of [`ORDER_MESSAGE_TAG`], the token identity,
+/// the `price` as a little-endian `u64`, and `maker_dest`.
+pub fn order_message(token_app: &App, order: &LimitOrder) -> B32 {
+    let hash = Sha256::new()
+        .chain_update(ORDER_MESSAGE_TAG)
+        .chain_update(token_app.identity.0)
+        .chain_update(order.price.to_le_bytes())
+        .chain_update(&order.maker_dest)
+        .finalize();
+    B32(hash.into())
+}
+
+/// Returns whether the transaction spends `token_app` tokens in the offered form.
+pub fn has_order_input(token_app: &App, tx: &Transaction) -> Result<bool, ContractError> {
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if matches!(token_amount(data)?, TokenAmount::Offered { .. }) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Validates the fill of the limit order spent by the transaction.
+///
+/// Exactly one order may be spent, and its signature must verify. The tokens
+/// neither carried into outputs under the same order nor returned to the
+/// maker's destination in the transferable form are filled, and the maker's
+/// destination must be paid at least `price` sats for each. The tokens must
+/// balance as in a transfer.
+pub fn can_fill_order(token_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let mut orders = Vec::new();
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if let TokenAmount::Offered { offered, order } = token_amount(data)? {
+            orders.push((offered, order));
+        }
+    }
+    let [(offered, order)] = orders.as_slice() else {
+        return Err(ContractError::OrderCountMismatch(orders.len()));
+    };
+    let maker = hex::decode(&order.maker)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or(ContractError::InvalidOrderSignature)?;
+    verify_signature(&B32(maker), &order_message(token_app, order), &order.signature)
+        .map_err(|_| ContractError::InvalidOrderSignature)?;
+
+    let coin_outs = tx.coin_outs.as_deref().unwrap_or_default();
+    let to_maker = |output: usize| {
+        coin_outs
+            .get(output)
+            .is_some_and(|destination| destination.dest == order.maker_dest)
+    };
+    let mut paid: u64 = 0;
+    for destination in coin_outs
+        .iter()
+        .filter(|destination| destination.dest == order.maker_dest)
+    {
+        paid = paid
+            .checked_add(destination.amount)
+            .ok_or(ContractError::TokenAmountOverflow)?;
+    }
+
+    // the remainder of the order keeps its terms.
+    let mut remainder: u64 = 0;
+    let mut returned: u64 = 0;
+    for (output, charms) in tx.outs.iter().enumerate() {
+        let Some(data) = charms.get(token_app) else {
+            continue;
+        };
+        let amount = token_amount(data)?;
+        let total = match &amount {
+            TokenAmount::Offered { order: carried, .. } if carried == order => &mut remainder,
+            // tokens returned in any other form may be bound to someone else.
+            TokenAmount::Transferable(_) if to_maker(output) => &mut returned,
+            _ => continue,
+        };
+        *total = total
+            .checked_add(amount.amount())
+            .ok_or(ContractError::TokenAmountOverflow)?;
+    }
+    // tokens returned to the maker are cancelled rather than filled.
+    let filled = offered
+        .checked_sub(remainder)
+        .ok_or(ContractError::InvalidOrderRemainder {
+            offered: *offered,
+            remainder,
+        })?
+        .saturating_sub(returned);
+    let price = filled
+        .checked_mul(order.price)
+        .ok_or(ContractError::TokenAmountOverflow)?;
+    if paid < price {
+        return Err(ContractError::OrderUnderpaid { price, paid });
+    }
+    can_transfer_token(token_app, tx, w)
+}
@@ -0,0 +1,128 @@
+//! Staking token outputs for rewards minted from the reserve.
+//!
+//! Tokens in the staked form record the block height their lock started at. An
+//! unstake returns the principal, plus a reward of `staking_rate` billionths of
+//! a token per staked token and block, minted from the `remaining` supply of
+//! the reserve NFT.
+//!
+//! The reward is a mint like any other: it spends the NFT and passes the same
+//! finalization, cap, emission and mint authority checks. The unstake height is
+//! proven by a timelocked input (see [`HeightProof`](crate::HeightProof)), but a
+//! proof only bounds a height from below, so nothing stops a stake from being
+//! backdated. Whoever approves the mint, the NFT holder or its mint authorities,
+//! checks the stake before paying its reward.
+
+use charms_sdk::data::{App, Data, Transaction};
+
+use crate::{
+    can_mint_token, can_transfer_token, input_nft_content, minted_token_amount, nft_app_for,
+    token_amount, ContractError, TokenAmount,
+};
+
+/// Scale of the `staking_rate` of the reserve NFT: a rate of
+/// `STAKING_RATE_SCALE` rewards one token per staked token and block.
+pub const STAKING_RATE_SCALE: u64 = 1_000_000_000;
+
+/// Returns whether the transaction spends `token_app` tokens in the staked form.
+pub fn has_stake_input(token_app: &App, tx: &Transaction) -> Result<bool, ContractError> {
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if matches!(token_amount(data)?, TokenAmount::Staked { .. }) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns whether the transaction creates `token_app` tokens in the staked form.
+pub fn has_stake_output(token_app: &App, tx: &Transaction) -> Result<bool, ContractError> {
+    for data in tx.outs.iter().filter_map(|charms| charms.get(token_app)) {
+        if matches!(token_amount(data)?, TokenAmount::Staked { .. }) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Validates a stake of tokens at `height`.
+///
+/// Every staked output must start its lock at the declared `height`, which
+/// the NFT holder or mint authorities check before rewarding the stake. The
+/// tokens must balance as in a transfer.
+pub fn can_stake(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+    height: u64,
+) -> Result<(), ContractError> {
+    for data in tx.outs.iter().filter_map(|charms| charms.get(token_app)) {
+        if let TokenAmount::Staked { since, .. } = token_amount(data)? {
+            if since != height {
+                return Err(ContractError::InvalidStakeHeight { height, since });
+            }
+        }
+    }
+    can_transfer_token(token_app, tx, w)
+}
+
+/// Validates an unstake at `height`.
+///
+/// Every staked input earns its reward for the blocks elapsed since its lock
+/// started. If the reserve NFT is spent, the transaction must mint exactly the
+/// total reward, validated as a mint by [`can_mint_token`] at `height`. Without
+/// the NFT, the principal is returned without a reward and the tokens must
+/// balance as in a transfer.
+pub fn can_unstake(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+    height: Option<u64>,
+) -> Result<(), ContractError> {
+    let height = height.ok_or(ContractError::MissingBlockHeight)?;
+    let mut stakes = Vec::new();
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if let TokenAmount::Staked { staked, since } = token_amount(data)? {
+            let blocks = height
+                .checked_sub(since)
+                .ok_or(ContractError::InvalidStakeHeight { height, since })?;
+            stakes.push((staked, blocks));
+        }
+    }
+    if stakes.is_empty() {
+        return Err(ContractError::MissingStakeInput);
+    }
+
+    let nft_app = nft_app_for(token_app);
+    let Ok(input_content) = input_nft_content(&nft_app, tx) else {
+        return can_transfer_token(token_app, tx, w);
+    };
+    let rate = input_content.staking_rate;
+    let reward: u128 = stakes
+        .iter()
+        .map(|&(staked, blocks)| {
+            (u128::from(staked) * u128::from(rate)).saturating_mul(u128::from(blocks))
+                / u128::from(STAKING_RATE_SCALE)
+        })
+        .fold(0, u128::saturating_add);
+    let reward = u64::try_from(reward).unwrap_or(u64::MAX);
+    if reward > input_content.remaining {
+        return Err(ContractError::RewardExceedsReserve {
+            reward,
+            remaining: input_content.remaining,
+        });
+    }
+
+    // the reward, and only the reward, is minted from the reserve.
+    let minted = minted_token_amount(token_app, tx)?;
+    if minted != reward {
+        return Err(ContractError::StakeRewardMismatch { reward, minted });
+    }
+    can_mint_token(token_app, tx, w, Some(height))
+}
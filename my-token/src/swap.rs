@@ -0,0 +1,111 @@
+//! Atomic swaps of tokens for other tokens or for bitcoin in a single spell.
+//!
+//! The maker commits a [`SwapOrder`] as the witness of the token app it offers.
+//! The transaction must give away exactly the offered tokens and pay the maker's
+//! destination script at least the wanted tokens or sats. Both legs settle in
+//! the same Bitcoin transaction, so neither party trusts the other: the maker
+//! approves the order by signing the inputs holding the offered tokens, and the
+//! taker by signing the inputs paying for them.
+
+use charms_sdk::data::{App, Data, NativeOutput, Transaction, B32, TOKEN};
+use serde::{Deserialize, Serialize};
+
+use crate::{can_transfer_token, token_amount, ContractError};
+
+/// Order of an atomic swap, supplied as the witness `w` of the offered token app.
+///
+/// Tokens of the offered app returned to `maker_dest` are the maker's change;
+/// all others are given to the taker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapOrder {
+    /// Amount of the offered token given to the taker
+    pub give: u64,
+    /// Identity of the wanted token of this contract, or `None` for bitcoin
+    #[serde(default)]
+    pub want_token: Option<B32>,
+    /// Amount of the wanted token, or of sats, the maker receives at least
+    pub want: u64,
+    /// Destination script of the maker, receiving the wanted token or sats
+    pub maker_dest: Vec<u8>,
+}
+
+/// Validates a swap of `token_app` tokens against the [`SwapOrder`] in `w`.
+///
+/// The offered tokens must balance as in a transfer, exactly `give` of them must
+/// go to destinations other than `maker_dest`, and `maker_dest` must receive at
+/// least `want` of the wanted token or sats. The wanted token's own contract
+/// validates its leg as a transfer.
+pub fn can_swap_token(token_app: &App, tx: &Transaction, w: &Data) -> Result<(), ContractError> {
+    let order: SwapOrder = w.value().map_err(|_| ContractError::InvalidSwapOrder)?;
+    let want_app = order.want_token.as_ref().map(|identity| App {
+        tag: TOKEN,
+        identity: identity.clone(),
+        vk: token_app.vk.clone(),
+    });
+    if want_app.as_ref() == Some(token_app) {
+        return Err(ContractError::InvalidSwapOrder);
+    }
+
+    // the offered tokens only change hands.
+    can_transfer_token(token_app, tx, w)?;
+
+    let mut given: u64 = 0;
+    let mut paid: u64 = 0;
+    for (output, charms) in tx.outs.iter().enumerate() {
+        let offered = charms.get(token_app);
+        let wanted = want_app.as_ref().and_then(|app| charms.get(app));
+        if offered.is_none() && wanted.is_none() && want_app.is_some() {
+            continue;
+        }
+        let destination = destination(tx, output)?;
+        let to_maker = destination.dest == order.maker_dest;
+        if let Some(data) = offered.filter(|_| !to_maker) {
+            given = given
+                .checked_add(token_amount(data)?.amount())
+                .ok_or(ContractError::TokenAmountOverflow)?;
+        }
+        let received = match (wanted, &want_app) {
+            (Some(data), _) => token_amount(data)?.amount(),
+            (None, None) => destination.amount,
+            (None, Some(_)) => 0,
+        };
+        if to_maker {
+            paid = paid
+                .checked_add(received)
+                .ok_or(ContractError::TokenAmountOverflow)?;
+        }
+    }
+    // with bitcoin wanted, outputs without charms may pay the maker too.
+    if want_app.is_none() {
+        for output in tx.outs.len()..tx.coin_outs.as_ref().map_or(0, Vec::len) {
+            let destination = destination(tx, output)?;
+            if destination.dest == order.maker_dest {
+                paid = paid
+                    .checked_add(destination.amount)
+                    .ok_or(ContractError::TokenAmountOverflow)?;
+            }
+        }
+    }
+
+    if given != order.give {
+        return Err(ContractError::SwapGiveMismatch {
+            order: order.give,
+            given,
+        });
+    }
+    if paid < order.want {
+        return Err(ContractError::SwapUnderpaid {
+            wanted: order.want,
+            paid,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the native output at index `output` of the transaction.
+fn destination(tx: &Transaction, output: usize) -> Result<&NativeOutput, ContractError> {
+    tx.coin_outs
+        .as_ref()
+        .and_then(|coin_outs| coin_outs.get(output))
+        .ok_or(ContractError::MissingDestination(output))
+}
@@ -0,0 +1,125 @@
+//! Block heights proven by timelocked inputs.
+//!
+//! Charms transactions do not expose the height they confirm at, but Bitcoin
+//! enforces the timelocks of the scripts they spend. An input whose P2WSH
+//! witness script starts with `<height> OP_CHECKLOCKTIMEVERIFY OP_DROP` can only
+//! be spent by a transaction with an `nLockTime` of at least `height`, which is
+//! only mined above that height. A [`HeightProof`] reveals such a script, so a
+//! height declared in the public input can be checked against it.
+
+use charms_sdk::data::Transaction;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ContractError;
+
+/// `nLockTime` values from this one on are timestamps rather than block heights.
+pub const LOCKTIME_THRESHOLD: u64 = 500_000_000;
+
+/// `OP_0`, pushing an empty number.
+const OP_0: u8 = 0x00;
+/// `OP_1`, pushing the number one; `OP_2` to `OP_16` follow it.
+const OP_1: u8 = 0x51;
+/// `OP_16`, pushing the number sixteen.
+const OP_16: u8 = 0x60;
+/// `OP_CHECKLOCKTIMEVERIFY`, failing unless `nLockTime` reaches the top number.
+const OP_CHECKLOCKTIMEVERIFY: u8 = 0xb1;
+/// `OP_DROP`, removing the top stack item.
+const OP_DROP: u8 = 0x75;
+/// Largest number `OP_CHECKLOCKTIMEVERIFY` accepts is 5 bytes long.
+const MAX_LOCKTIME_LEN: usize = 5;
+
+/// Proof that a transaction confirms above a block height, supplied in the
+/// [`PublicInput`](crate::PublicInput).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeightProof {
+    /// Index of the timelocked input in the transaction
+    pub input: usize,
+    /// Hex-encoded witness script of the input, see [`timelock_script`]
+    pub witness_script: String,
+}
+
+/// Returns the P2WSH script pubkey committing to `witness_script`.
+pub fn p2wsh_script_pubkey(witness_script: &[u8]) -> Vec<u8> {
+    [&[OP_0, 32][..], &Sha256::digest(witness_script)].concat()
+}
+
+/// Returns `script` behind a lock that keeps it unspendable up to `height`.
+///
+/// The lock pushes `height` as a minimally encoded number, followed by
+/// `OP_CHECKLOCKTIMEVERIFY OP_DROP`.
+#[allow(clippy::cast_possible_truncation)]
+pub fn timelock_script(height: u64, script: &[u8]) -> Vec<u8> {
+    let mut number = height.to_le_bytes().to_vec();
+    while number.last() == Some(&0) {
+        number.pop();
+    }
+    let mut lock = match number[..] {
+        [] => vec![OP_0],
+        [small @ 1..=16] => vec![OP_1 - 1 + small],
+        [.., last] => {
+            // a set top bit would make the number negative.
+            if last & 0x80 != 0 {
+                number.push(0);
+            }
+            // a `u64` takes at most 9 bytes, so its length fits the push opcode.
+            [&[number.len() as u8][..], &number].concat()
+        },
+    };
+    lock.extend([OP_CHECKLOCKTIMEVERIFY, OP_DROP]);
+    lock.extend(script);
+    lock
+}
+
+/// Returns the block height the input revealed by `proof` is locked to.
+///
+/// The input's destination script in `tx.coin_ins` must be the P2WSH of the
+/// witness script, and the witness script must start with a block height lock.
+pub fn proven_height(tx: &Transaction, proof: &HeightProof) -> Result<u64, ContractError> {
+    let script =
+        hex::decode(&proof.witness_script).map_err(|_| ContractError::InvalidHeightProof)?;
+    let spent = tx
+        .coin_ins
+        .as_ref()
+        .and_then(|coin_ins| coin_ins.get(proof.input))
+        .ok_or(ContractError::InvalidHeightProof)?;
+    if spent.dest != p2wsh_script_pubkey(&script) {
+        return Err(ContractError::InvalidHeightProof);
+    }
+    let (height, rest) = match script.split_first() {
+        Some((&OP_0, rest)) => (0, rest),
+        Some((&opcode @ OP_1..=OP_16, rest)) => (u64::from(opcode - OP_1 + 1), rest),
+        Some((&len, rest)) if (1..=MAX_LOCKTIME_LEN).contains(&(len as usize)) => {
+            let (number, rest) = rest
+                .split_at_checked(len as usize)
+                .ok_or(ContractError::InvalidHeightProof)?;
+            // negative numbers fail OP_CHECKLOCKTIMEVERIFY.
+            if number.last().is_some_and(|byte| byte & 0x80 != 0) {
+                return Err(ContractError::InvalidHeightProof);
+            }
+            let height = number
+                .iter()
+                .rev()
+                .fold(0, |height, &byte| height << 8 | u64::from(byte));
+            (height, rest)
+        },
+        _ => return Err(ContractError::InvalidHeightProof),
+    };
+    if !rest.starts_with(&[OP_CHECKLOCKTIMEVERIFY, OP_DROP]) || height >= LOCKTIME_THRESHOLD {
+        return Err(ContractError::InvalidHeightProof);
+    }
+    Ok(height)
+}
+
+/// Checks that `proof` shows the transaction confirms above the declared `height`.
+pub fn check_height(
+    tx: &Transaction,
+    proof: Option<&HeightProof>,
+    height: u64,
+) -> Result<(), ContractError> {
+    let proven = proven_height(tx, proof.ok_or(ContractError::MissingHeightProof)?)?;
+    if height > proven {
+        return Err(ContractError::HeightNotProven { height, proven });
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+//! Vesting token outputs for team and investor allocations.
+//!
+//! Tokens in the vesting form carry a [`VestingSchedule`] that releases `total`
+//! linearly over `duration` blocks from `start`, with nothing released before
+//! the cliff. A release pays the newly vested tokens to the beneficiary and
+//! re-emits the schedule with `released` advanced, until all of it is released.
+//! Its height is proven by a timelocked input (see
+//! [`HeightProof`](crate::HeightProof)), and as the vested amount only grows with
+//! the height, the proof bounding it from below is all a release needs.
+
+use charms_sdk::data::{App, Data, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::{can_transfer_token, token_amount, ContractError, TokenAmount};
+
+/// Linear vesting schedule of a token allocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VestingSchedule {
+    /// Destination script receiving the released tokens
+    pub beneficiary: Vec<u8>,
+    /// Number of tokens vested over the whole schedule
+    pub total: u64,
+    /// Block height the schedule starts at
+    pub start: u64,
+    /// Number of blocks after `start` before anything vests
+    pub cliff: u64,
+    /// Number of blocks after `start` until everything has vested
+    pub duration: u64,
+    /// Number of tokens released so far
+    #[serde(default)]
+    pub released: u64,
+}
+
+impl VestingSchedule {
+    /// Returns the number of tokens vested at `height`.
+    ///
+    /// Nothing vests before `start + cliff`. From then on, `total` vests in
+    /// proportion to the blocks elapsed since `start`, and all of it once
+    /// `duration` blocks have elapsed.
+    pub fn vested_at(&self, height: u64) -> u64 {
+        if height < self.start.saturating_add(self.cliff) {
+            return 0;
+        }
+        let elapsed = height - self.start;
+        if elapsed >= self.duration {
+            return self.total;
+        }
+        let vested = u128::from(self.total) * u128::from(elapsed) / u128::from(self.duration);
+        u64::try_from(vested).unwrap_or(self.total)
+    }
+
+    /// Returns the number of tokens still held by the schedule.
+    pub const fn unreleased(&self) -> u64 {
+        self.total.saturating_sub(self.released)
+    }
+}
+
+/// Returns whether the transaction spends `token_app` tokens in the vesting form.
+pub fn has_vesting_input(token_app: &App, tx: &Transaction) -> Result<bool, ContractError> {
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if matches!(token_amount(data)?, TokenAmount::Vesting { .. }) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Validates a release of vested tokens at `height`.
+///
+/// Exactly one schedule may be spent. Unless it is released in full, it must be
+/// re-emitted with only `released` advanced, by no more than the tokens vested
+/// at `height` allow, and the released tokens must be paid to the beneficiary
+/// in the transferable form. The tokens must balance as in a transfer.
+pub fn can_release_vested(
+    token_app: &App,
+    tx: &Transaction,
+    w: &Data,
+    height: Option<u64>,
+) -> Result<(), ContractError> {
+    let height = height.ok_or(ContractError::MissingBlockHeight)?;
+    let input_schedule = spent_schedule(token_app, tx)?;
+
+    let mut outputs = Vec::new();
+    for data in tx.outs.iter().filter_map(|charms| charms.get(token_app)) {
+        if let TokenAmount::Vesting { vesting } = token_amount(data)? {
+            outputs.push(vesting);
+        }
+    }
+    let released = match outputs.as_slice() {
+        [] => input_schedule.total,
+        [output_schedule] => output_schedule.released,
+        _ => return Err(ContractError::VestingStateChanged),
+    };
+    let expected_schedule = VestingSchedule {
+        released,
+        ..input_schedule.clone()
+    };
+    let re_emitted = outputs
+        .first()
+        .is_none_or(|output| **output == expected_schedule);
+    if !re_emitted || released < input_schedule.released || released > input_schedule.total {
+        return Err(ContractError::VestingStateChanged);
+    }
+    let vested = input_schedule.vested_at(height);
+    if released > vested {
+        return Err(ContractError::VestingExceeded { vested, released });
+    }
+
+    // the newly released tokens go to the beneficiary, free to spend.
+    let mut paid: u64 = 0;
+    for (output, charms) in tx.outs.iter().enumerate() {
+        let to_beneficiary = tx
+            .coin_outs
+            .as_ref()
+            .and_then(|coin_outs| coin_outs.get(output))
+            .is_some_and(|destination| destination.dest == input_schedule.beneficiary);
+        let Some(data) = charms.get(token_app).filter(|_| to_beneficiary) else {
+            continue;
+        };
+        if let TokenAmount::Transferable(amount) = token_amount(data)? {
+            paid = paid
+                .checked_add(amount)
+                .ok_or(ContractError::TokenAmountOverflow)?;
+        }
+    }
+    let releasing = released - input_schedule.released;
+    if paid < releasing {
+        return Err(ContractError::VestingPayoutMismatch {
+            released: releasing,
+            paid,
+        });
+    }
+    can_transfer_token(token_app, tx, w)
+}
+
+/// Reads the only vesting schedule spent by the transaction.
+fn spent_schedule(token_app: &App, tx: &Transaction) -> Result<VestingSchedule, ContractError> {
+    let mut schedules = Vec::new();
+    for data in tx
+        .ins
+        .iter()
+        .filter_map(|(_, charms)| charms.get(token_app))
+    {
+        if let TokenAmount::Vesting { vesting } = token_amount(data)? {
+            schedules.push(vesting);
+        }
+    }
+    match <[_; 1]>::try_from(schedules) {
+        Ok([schedule]) => Ok(*schedule),
+        Err(schedules) => Err(ContractError::VestingCountMismatch(schedules.len())),
+    }
+}
@@ -1,10 +1,148 @@
 //! Integration tests for the NFT token contract.
 //!
 //! These tests verify the core functionality of the contract including
-//! hash operations and `NftContent` data structure behavior.
+//! hash operations, `NftContent` data structure behavior and the
+//! `ContractError` reported by `validate` for each rejection path.
 
-use charms_sdk::data::{Data, UtxoId};
-use my_token::{hash, NftContent};
+use std::collections::BTreeMap;
+
+use charms_sdk::data::{App, Charms, Data, NativeOutput, Transaction, UtxoId, B32, NFT, TOKEN};
+use k256::schnorr::SigningKey;
+use my_token::{
+    airdrop_leaf, app_contract, check_ticker, child_identity, collection_identity, hash, leaf_hash,
+    merkle_proof, merkle_root, mint_message, order_message, p2wsh_script_pubkey, proven_height,
+    timelock_script, validate, verify_proof, Airdrop, AirdropClaim, ChildContent, ClaimWitness,
+    CollectionContent, CollectionItem, ContractError, ContractInput, EmissionSchedule, HeightProof,
+    Htlc, HtlcWitness, LimitOrder, MintWitness, MultisigSignature, NftContent, NftState,
+    PublicInput, SwapOrder, TokenAmount, TransferWitness, VestingSchedule, LOCKTIME_THRESHOLD,
+    MAX_DECIMALS, MAX_DESCRIPTION_LEN, MAX_NAME_LEN, MAX_TICKER_LEN, MAX_URI_LEN,
+    STAKING_RATE_SCALE,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// UTXO spent to mint the NFT in the contract tests.
+const MINT_UTXO: &str = "dc78b09d767c8565c4a58a95e7ad5ee22b28fc1685535056a395dc94929cdd5f:1";
+
+/// Builds an app with the given tag whose identity is derived from [`MINT_UTXO`].
+fn app(tag: char) -> App {
+    App {
+        tag,
+        identity: hash(MINT_UTXO),
+        vk: B32([7; 32]),
+    }
+}
+
+/// Builds a UTXO ID with a distinct transaction hash for each `n`.
+fn utxo(n: u8) -> UtxoId {
+    UtxoId::from_str(&format!("{}:0", format!("{n:02x}").repeat(32))).unwrap()
+}
+
+/// Builds a set of charms from `(app, data)` pairs.
+fn charms(entries: &[(&App, Data)]) -> Charms {
+    entries
+        .iter()
+        .map(|(app, data)| ((*app).clone(), data.clone()))
+        .collect()
+}
+
+/// Builds a transaction from its inputs and outputs.
+const fn tx(ins: Vec<(UtxoId, Charms)>, outs: Vec<Charms>) -> Transaction {
+    Transaction {
+        ins,
+        refs: Vec::new(),
+        outs,
+        coin_ins: None,
+        coin_outs: None,
+        prev_txs: BTreeMap::new(),
+        app_public_inputs: BTreeMap::new(),
+    }
+}
+
+fn nft(ticker: &str, remaining: u64) -> Data {
+    Data::from(&NftContent {
+        ticker: ticker.to_string(),
+        remaining,
+        ..Default::default()
+    })
+}
+
+fn tokens(amount: u64) -> Data {
+    Data::from(&amount)
+}
+
+fn witness() -> Data {
+    Data::from(&MINT_UTXO.to_string())
+}
+
+/// Builds the public input declaring `op`.
+fn declare(op: ContractInput) -> Data {
+    Data::from(&PublicInput {
+        version: 1,
+        op,
+        height_proof: None,
+    })
+}
+
+/// Builds the public input declaring `op`, with its height proven by the first
+/// input of a transaction built with [`timelocked`] at `height`.
+fn declare_proven(op: ContractInput, height: u64) -> Data {
+    Data::from(&PublicInput {
+        version: 1,
+        op,
+        height_proof: Some(HeightProof {
+            input: 0,
+            witness_script: hex::encode(lock_script(height)),
+        }),
+    })
+}
+
+/// Builds the public input declaring a mint at `height`, proven by [`timelocked`].
+fn mint_at(height: u64) -> Data {
+    declare_proven(
+        ContractInput::Mint {
+            height: Some(height),
+        },
+        height,
+    )
+}
+
+/// Returns the witness script of an input anyone can spend above `height`.
+fn lock_script(height: u64) -> Vec<u8> {
+    timelock_script(height, &[0x51])
+}
+
+/// Adds an input that can only be spent above `height` in front of the inputs
+/// of `tx`, to prove its height.
+fn timelocked(mut tx: Transaction, height: u64) -> Transaction {
+    let spent = |dest: Vec<u8>| NativeOutput { amount: 1000, dest };
+    let mut coin_ins = tx
+        .coin_ins
+        .take()
+        .unwrap_or_else(|| vec![spent(b"spender".to_vec()); tx.ins.len()]);
+    coin_ins.insert(0, spent(p2wsh_script_pubkey(&lock_script(height))));
+    tx.coin_ins = Some(coin_ins);
+    tx.ins.insert(0, (utxo(0xff), Charms::new()));
+    tx
+}
+
+/// Builds a transaction that spends the NFT with `remaining_in` and `tokens_in` tokens,
+/// and recreates it with `remaining_out` next to `tokens_out` tokens.
+fn mint_tx(remaining_in: u64, tokens_in: u64, remaining_out: u64, tokens_out: u64) -> Transaction {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let mut ins = vec![(utxo(1), charms(&[(&nft_app, nft("MY-TOKEN", remaining_in))]))];
+    if tokens_in > 0 {
+        ins.push((utxo(2), charms(&[(&token_app, tokens(tokens_in))])));
+    }
+    tx(
+        ins,
+        vec![
+            charms(&[(&token_app, tokens(tokens_out))]),
+            charms(&[(&nft_app, nft("MY-TOKEN", remaining_out))]),
+        ],
+    )
+}
 
 /// Tests the SHA-256 hash function.
 ///
@@ -51,6 +189,7 @@ fn test_nft_content_serialization() {
     let content = NftContent {
         ticker: "TEST".to_string(),
         remaining: 1000,
+        ..Default::default()
     };
 
     // Serialize to Data using From trait
@@ -71,6 +210,7 @@ fn test_nft_content_zero_remaining() {
     let content = NftContent {
         ticker: "ZERO".to_string(),
         remaining: 0,
+        ..Default::default()
     };
 
     let data = Data::from(&content);
@@ -87,6 +227,7 @@ fn test_nft_content_max_remaining() {
     let content = NftContent {
         ticker: "MAX".to_string(),
         remaining: u64::MAX,
+        ..Default::default()
     };
 
     let data = Data::from(&content);
@@ -103,6 +244,7 @@ fn test_nft_content_clone() {
     let content = NftContent {
         ticker: "CLONE".to_string(),
         remaining: 5000,
+        ..Default::default()
     };
 
     let cloned = content.clone();
@@ -119,6 +261,7 @@ fn test_nft_content_debug() {
     let content = NftContent {
         ticker: "DEBUG".to_string(),
         remaining: 100,
+        ..Default::default()
     };
 
     let debug_output = format!("{content:?}");
@@ -129,11 +272,13 @@ fn test_nft_content_debug() {
 /// Tests that empty ticker is accepted.
 ///
 /// Verifies that `NftContent` can be created with an empty ticker string.
+/// The contract itself refuses to mint such an NFT (see `check_ticker`).
 #[test]
 fn test_nft_content_empty_ticker() {
     let content = NftContent {
         ticker: String::new(),
         remaining: 1000,
+        ..Default::default()
     };
 
     assert_eq!(content.ticker, "");
@@ -143,12 +288,14 @@ fn test_nft_content_empty_ticker() {
 /// Tests `NftContent` with long ticker string.
 ///
 /// Verifies that `NftContent` can handle arbitrarily long ticker strings.
+/// The contract itself refuses to mint such an NFT (see `check_ticker`).
 #[test]
 fn test_nft_content_long_ticker() {
     let long_ticker = "A".repeat(1000);
     let content = NftContent {
         ticker: long_ticker.clone(),
         remaining: 500,
+        ..Default::default()
     };
 
     let data = Data::from(&content);
@@ -266,11 +413,3882 @@ fn test_testnet4_utxo_id_hashing() {
 fn test_different_indices_produce_different_identities() {
     let base_tx = "dc78b09d767c8565c4a58a95e7ad5ee22b28fc1685535056a395dc94929cdd5f";
 
-    let identity0 = hash(&format!("{}:0", base_tx));
-    let identity1 = hash(&format!("{}:1", base_tx));
-    let identity2 = hash(&format!("{}:2", base_tx));
+    let identity0 = hash(&format!("{base_tx}:0"));
+    let identity1 = hash(&format!("{base_tx}:1"));
+    let identity2 = hash(&format!("{base_tx}:2"));
 
     assert_ne!(identity0, identity1);
     assert_ne!(identity1, identity2);
     assert_ne!(identity0, identity2);
 }
+
+/// Tests that a well-formed NFT mint is accepted.
+///
+/// Mirrors `spells/mint-nft.yaml`: spends the witness UTXO and creates one NFT.
+#[test]
+fn test_validate_mint_nft() {
+    let nft_app = app(NFT);
+    let mint = tx(
+        vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())],
+        vec![charms(&[(&nft_app, nft("MY-TOKEN", 100_000))])],
+    );
+
+    assert_eq!(validate(&nft_app, &mint, &Data::empty(), &witness()), Ok(()));
+    assert!(app_contract(&nft_app, &mint, &Data::empty(), &witness()));
+}
+
+/// Tests that an unknown app tag is rejected instead of panicking.
+#[test]
+fn test_validate_unknown_tag() {
+    let other_app = app('x');
+    let empty = tx(vec![], vec![]);
+
+    assert_eq!(
+        validate(&other_app, &empty, &Data::empty(), &Data::empty()),
+        Err(ContractError::UnknownTag('x'))
+    );
+    assert!(!app_contract(&other_app, &empty, &Data::empty(), &Data::empty()));
+}
+
+/// Tests that a public input other than a [`PublicInput`] is rejected.
+#[test]
+fn test_validate_invalid_public_input() {
+    for x in [Data::from(&42u64), Data::from(&"mint".to_string())] {
+        assert_eq!(
+            validate(&app(NFT), &tx(vec![], vec![]), &x, &witness()),
+            Err(ContractError::InvalidPublicInput)
+        );
+    }
+}
+
+/// Tests that a public input of an unknown version is rejected.
+#[test]
+fn test_validate_unsupported_input_version() {
+    let x = Data::from(&PublicInput {
+        version: 2,
+        op: ContractInput::Transfer,
+        height_proof: None,
+    });
+
+    assert_eq!(
+        validate(&app(TOKEN), &tx(vec![], vec![]), &x, &Data::empty()),
+        Err(ContractError::UnsupportedInputVersion(2))
+    );
+}
+
+/// Tests that the declared operations accept the transactions performing them.
+#[test]
+fn test_validate_declared_operations() {
+    let token_app = app(TOKEN);
+    let nft_app = app(NFT);
+    let content = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 100_000,
+        ..Default::default()
+    };
+    let mint = declare(ContractInput::Mint { height: None });
+    let transfer = declare(ContractInput::Transfer);
+    let burn = declare(ContractInput::Burn);
+
+    assert_eq!(validate(&nft_app, &mint_nft_tx(&content), &mint, &witness()), Ok(()));
+    let mint_tokens = mint_tx(100_000, 0, 30_580, 69_420);
+    assert_eq!(validate(&token_app, &mint_tokens, &mint, &Data::empty()), Ok(()));
+    assert_eq!(validate(&nft_app, &mint_tokens, &mint, &Data::empty()), Ok(()));
+
+    let send = tx(
+        vec![(utxo(1), charms(&[(&token_app, tokens(69_420))]))],
+        vec![charms(&[(&token_app, tokens(69_420))])],
+    );
+    assert_eq!(validate(&token_app, &send, &transfer, &Data::empty()), Ok(()));
+    let move_nft = supply_tx(&content, 0, &content, 0);
+    assert_eq!(validate(&nft_app, &move_nft, &transfer, &Data::empty()), Ok(()));
+
+    let nft_out = NftContent {
+        burned: 420,
+        ..content.clone()
+    };
+    let burn_tokens = supply_tx(&content, 69_420, &nft_out, 69_000);
+    assert_eq!(validate(&token_app, &burn_tokens, &burn, &Data::empty()), Ok(()));
+    assert_eq!(validate(&nft_app, &burn_tokens, &burn, &Data::empty()), Ok(()));
+}
+
+/// Tests that a transaction is held to the declared operation only.
+#[test]
+fn test_validate_declared_operation_mismatch() {
+    let mint_tokens = mint_tx(100_000, 0, 30_580, 69_420);
+    let transfer = declare(ContractInput::Transfer);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint_tokens, &transfer, &Data::empty()),
+        Err(ContractError::UnbalancedTransfer {
+            input: 0,
+            output: 69_420
+        })
+    );
+    assert_eq!(
+        validate(&app(NFT), &mint_tokens, &transfer, &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+    assert_eq!(
+        validate(&app(TOKEN), &mint_tokens, &declare(ContractInput::Burn), &Data::empty()),
+        Err(ContractError::UnbalancedTransfer {
+            input: 0,
+            output: 69_420
+        })
+    );
+    assert_eq!(
+        validate(&app(NFT), &mint_tokens, &declare(ContractInput::UpdateMetadata), &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &mint_tokens,
+            &declare(ContractInput::UpdateMetadata),
+            &Data::empty()
+        ),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Tests the NFT minting rejections: witness format, identity and spent UTXO.
+#[test]
+fn test_validate_mint_nft_witness_errors() {
+    let nft_app = app(NFT);
+    let mint = tx(
+        vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())],
+        vec![charms(&[(&nft_app, nft("MY-TOKEN", 100_000))])],
+    );
+    let x = Data::empty();
+
+    assert_eq!(
+        validate(&nft_app, &mint, &x, &Data::empty()),
+        Err(ContractError::WitnessNotString)
+    );
+    assert_eq!(
+        validate(&nft_app, &mint, &x, &Data::from(&"utxo1:0".to_string())),
+        Err(ContractError::IdentityMismatch)
+    );
+
+    let not_spent = tx(vec![(utxo(1), Charms::new())], mint.outs);
+    assert_eq!(
+        validate(&nft_app, &not_spent, &x, &witness()),
+        Err(ContractError::WitnessUtxoNotSpent)
+    );
+}
+
+/// Tests that a witness whose hash matches but which is not a UTXO ID is rejected.
+#[test]
+fn test_validate_mint_nft_invalid_witness_utxo_id() {
+    let w_str = "not-a-utxo-id".to_string();
+    let nft_app = App {
+        identity: hash(&w_str),
+        ..app(NFT)
+    };
+    let mint = tx(vec![], vec![charms(&[(&nft_app, nft("MY-TOKEN", 1))])]);
+
+    assert_eq!(
+        validate(&nft_app, &mint, &Data::empty(), &Data::from(&w_str)),
+        Err(ContractError::InvalidWitnessUtxoId(w_str))
+    );
+}
+
+/// Tests that an NFT mint must create exactly one NFT with valid content.
+#[test]
+fn test_validate_mint_nft_output_errors() {
+    let nft_app = app(NFT);
+    let ins = vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())];
+    let x = Data::empty();
+
+    let two = tx(
+        ins.clone(),
+        vec![
+            charms(&[(&nft_app, nft("MY-TOKEN", 1))]),
+            charms(&[(&nft_app, nft("MY-TOKEN", 1))]),
+        ],
+    );
+    assert_eq!(
+        validate(&nft_app, &two, &x, &witness()),
+        Err(ContractError::NftCountMismatch(2))
+    );
+
+    let malformed = tx(ins, vec![charms(&[(&nft_app, Data::from(&"oops".to_string()))])]);
+    assert_eq!(
+        validate(&nft_app, &malformed, &x, &witness()),
+        Err(ContractError::InvalidNftContent)
+    );
+}
+
+/// Tests that minting tokens against the NFT supply is accepted by both apps.
+///
+/// Mirrors `spells/mint-token.yaml`.
+#[test]
+fn test_validate_mint_token() {
+    let mint = mint_tx(100_000, 0, 30_580, 69_420);
+
+    assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Tests that minting more or fewer tokens than the supply decrease is rejected.
+#[test]
+fn test_validate_mint_amount_mismatch() {
+    let mint = mint_tx(100_000, 0, 30_580, 70_000);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintAmountMismatch {
+            minted: 70_000,
+            supply_decrease: 69_420
+        })
+    );
+}
+
+/// Tests that the NFT remaining supply cannot increase.
+#[test]
+fn test_validate_supply_increased() {
+    let mint = mint_tx(100, 0, 200, 0);
+
+    assert_eq!(
+        validate(&app(NFT), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::SupplyIncreased {
+            incoming: 100,
+            outgoing: 200
+        })
+    );
+}
+
+/// Tests that a pure token transfer is accepted and an unbalanced one rejected.
+///
+/// Mirrors `spells/send.yaml`.
+#[test]
+fn test_validate_transfer_token() {
+    let token_app = app(TOKEN);
+    let ins = vec![(utxo(1), charms(&[(&token_app, tokens(69_420))]))];
+    let x = Data::empty();
+
+    let send = tx(
+        ins.clone(),
+        vec![
+            charms(&[(&token_app, tokens(420))]),
+            charms(&[(&token_app, tokens(69_000))]),
+        ],
+    );
+    assert_eq!(validate(&token_app, &send, &x, &Data::empty()), Ok(()));
+
+    let inflate = tx(ins, vec![charms(&[(&token_app, tokens(70_000))])]);
+    assert_eq!(
+        validate(&token_app, &inflate, &x, &Data::empty()),
+        Err(ContractError::UnbalancedTransfer {
+            input: 69_420,
+            output: 70_000
+        })
+    );
+}
+
+/// Tests that a transfer moving no tokens is rejected.
+#[test]
+fn test_validate_empty_transfer() {
+    assert_eq!(
+        validate(&app(TOKEN), &tx(vec![], vec![]), &Data::empty(), &Data::empty()),
+        Err(ContractError::EmptyTransfer)
+    );
+}
+
+/// Tests that a malformed token amount is rejected.
+#[test]
+fn test_validate_invalid_token_amount() {
+    let token_app = app(TOKEN);
+    let bad = tx(vec![(utxo(1), charms(&[(&token_app, Data::from(&"ten".to_string()))]))], vec![]);
+
+    assert_eq!(
+        validate(&token_app, &bad, &Data::empty(), &Data::empty()),
+        Err(ContractError::InvalidTokenAmount)
+    );
+}
+
+/// Tests that the NFT can be moved unchanged alongside a token transfer.
+///
+/// Mirrors `spells/transfer.yaml`.
+#[test]
+fn test_validate_preserve_nft() {
+    let moved = mint_tx(30_580, 69_420, 30_580, 69_420);
+
+    assert_eq!(validate(&app(NFT), &moved, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(TOKEN), &moved, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Tests that spending the NFT with supply left, without recreating it, is rejected.
+#[test]
+fn test_validate_missing_output_nft() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let spend = tx(vec![(utxo(1), charms(&[(&nft_app, nft("MY-TOKEN", 10))]))], vec![]);
+
+    assert_eq!(
+        validate(&nft_app, &spend, &Data::empty(), &Data::empty()),
+        Err(ContractError::NftNotBurnable { remaining: 10 })
+    );
+
+    let mint = tx(
+        vec![(utxo(1), charms(&[(&nft_app, nft("MY-TOKEN", 10))]))],
+        vec![charms(&[(&token_app, tokens(10))])],
+    );
+    assert_eq!(
+        validate(&token_app, &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MissingOutputNft)
+    );
+}
+
+/// Tests that an exhausted NFT can be burned, like `spells/burn-nft.yaml`.
+#[test]
+fn test_validate_burn_exhausted_nft() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let burn = tx(
+        vec![
+            (utxo(1), charms(&[(&nft_app, nft("MY-TOKEN", 0))])),
+            (utxo(2), charms(&[(&token_app, tokens(69_420))])),
+        ],
+        vec![charms(&[(&token_app, tokens(69_420))])],
+    );
+
+    assert_eq!(validate(&nft_app, &burn, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&token_app, &burn, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&nft_app, &burn, &declare(ContractInput::Burn), &Data::empty()), Ok(()));
+}
+
+/// Tests that a `burnable` NFT can be burned with supply left in its reserve.
+#[test]
+fn test_validate_burn_burnable_nft() {
+    let nft_app = app(NFT);
+    let content = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+        burnable: true,
+        ..Default::default()
+    };
+    let burn = tx(vec![(utxo(1), charms(&[(&nft_app, Data::from(&content))]))], vec![]);
+
+    assert_eq!(validate(&nft_app, &burn, &Data::empty(), &Data::empty()), Ok(()));
+
+    let locked = NftContent {
+        burnable: false,
+        ..content
+    };
+    let burn = tx(vec![(utxo(1), charms(&[(&nft_app, Data::from(&locked))]))], vec![]);
+    assert_eq!(
+        validate(&nft_app, &burn, &declare(ContractInput::Burn), &Data::empty()),
+        Err(ContractError::NftNotBurnable { remaining: 30_580 })
+    );
+}
+
+/// Tests that `ContractError` renders a readable message.
+#[test]
+fn test_contract_error_display() {
+    let err = ContractError::MintAmountMismatch {
+        minted: 5,
+        supply_decrease: 3,
+    };
+
+    assert_eq!(
+        err.to_string(),
+        "minted amount (5) must equal the decrease in remaining supply (3)"
+    );
+}
+
+/// Builds a transaction that spends the NFT in state `nft_in` with `tokens_in` tokens,
+/// and recreates it in state `nft_out` next to `tokens_out` tokens.
+fn supply_tx(
+    nft_in: &NftContent,
+    tokens_in: u64,
+    nft_out: &NftContent,
+    tokens_out: u64,
+) -> Transaction {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    tx(
+        vec![
+            (utxo(1), charms(&[(&nft_app, Data::from(nft_in))])),
+            (utxo(2), charms(&[(&token_app, tokens(tokens_in))])),
+        ],
+        vec![
+            charms(&[(&token_app, tokens(tokens_out))]),
+            charms(&[(&nft_app, Data::from(nft_out))]),
+        ],
+    )
+}
+
+/// Tests that `NftContent` stored in the original two-field layout still deserializes.
+#[test]
+fn test_nft_content_legacy_layout() {
+    #[derive(serde::Serialize)]
+    struct LegacyNftContent {
+        ticker: String,
+        remaining: u64,
+    }
+
+    let data = Data::from(&LegacyNftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+    });
+    let content: NftContent = data.value().expect("Should deserialize legacy layout");
+
+    assert_eq!(content.remaining, 30_580);
+    assert_eq!(content.burned, 0);
+    assert!(!content.recycle_burns);
+}
+
+/// Tests that burning tokens retires them into the NFT `burned` counter.
+#[test]
+fn test_validate_burn_retires_tokens() {
+    let nft_in = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+        burned: 10,
+        ..Default::default()
+    };
+    let nft_out = NftContent {
+        burned: 430,
+        ..nft_in.clone()
+    };
+    let burn = supply_tx(&nft_in, 69_420, &nft_out, 69_000);
+
+    assert_eq!(validate(&app(TOKEN), &burn, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(NFT), &burn, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Tests that burning tokens returns them to `remaining` when `recycle_burns` is set.
+#[test]
+fn test_validate_burn_recycles_tokens() {
+    let nft_in = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+        recycle_burns: true,
+        ..Default::default()
+    };
+    let nft_out = NftContent {
+        remaining: 31_000,
+        ..nft_in.clone()
+    };
+    let burn = supply_tx(&nft_in, 69_420, &nft_out, 69_000);
+
+    assert_eq!(validate(&app(TOKEN), &burn, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(NFT), &burn, &Data::empty(), &Data::empty()), Ok(()));
+
+    // recycled burns are not counted as retired
+    let counted = NftContent {
+        burned: 420,
+        ..nft_out
+    };
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &supply_tx(&nft_in, 69_420, &counted, 69_000),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::BurnedCounterMismatch {
+            expected: 0,
+            actual: 420
+        })
+    );
+}
+
+/// Tests that a burn must be accounted for in the NFT state.
+#[test]
+fn test_validate_burn_accounting_mismatch() {
+    let nft_in = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+        ..Default::default()
+    };
+
+    let unrecorded = supply_tx(&nft_in, 69_420, &nft_in, 69_000);
+    assert_eq!(
+        validate(&app(TOKEN), &unrecorded, &Data::empty(), &Data::empty()),
+        Err(ContractError::BurnedCounterMismatch {
+            expected: 420,
+            actual: 0
+        })
+    );
+
+    let recycled = NftContent {
+        remaining: 31_000,
+        burned: 420,
+        ..nft_in.clone()
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&nft_in, 69_420, &recycled, 69_000),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::BurnRemainingMismatch {
+            expected: 30_580,
+            actual: 31_000
+        })
+    );
+}
+
+/// Tests that a burn cannot flip the burn mode of the NFT.
+#[test]
+fn test_validate_burn_cannot_change_mode() {
+    let nft_in = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+        ..Default::default()
+    };
+    let nft_out = NftContent {
+        burned: 420,
+        recycle_burns: true,
+        ..nft_in.clone()
+    };
+
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &supply_tx(&nft_in, 69_420, &nft_out, 69_000),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Tests that a recycled burn overflowing `remaining` is rejected.
+#[test]
+fn test_validate_burn_supply_overflow() {
+    let nft_in = NftContent {
+        ticker: "MAX".to_string(),
+        remaining: u64::MAX,
+        recycle_burns: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        validate(&app(TOKEN), &supply_tx(&nft_in, 10, &nft_in, 0), &Data::empty(), &Data::empty()),
+        Err(ContractError::SupplyOverflow)
+    );
+}
+
+/// Tests that tokens cannot be burned without the managing NFT.
+#[test]
+fn test_validate_burn_requires_nft() {
+    let token_app = app(TOKEN);
+    let burn = tx(
+        vec![(utxo(1), charms(&[(&token_app, tokens(69_420))]))],
+        vec![charms(&[(&token_app, tokens(69_000))])],
+    );
+
+    assert_eq!(
+        validate(&token_app, &burn, &Data::empty(), &Data::empty()),
+        Err(ContractError::UnbalancedTransfer {
+            input: 69_420,
+            output: 69_000
+        })
+    );
+}
+
+/// Tests that minting and preserving the NFT cannot tamper with the burn accounting.
+#[test]
+fn test_validate_burn_accounting_is_preserved() {
+    let nft_in = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 100_000,
+        burned: 5,
+        ..Default::default()
+    };
+    let reset = NftContent {
+        remaining: 30_580,
+        burned: 0,
+        ..nft_in.clone()
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&nft_in, 1, &reset, 69_421),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+
+    let reset = NftContent {
+        burned: 0,
+        ..nft_in.clone()
+    };
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 1, &reset, 1), &Data::empty(), &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Tests that the whole reserve can be minted when `remaining` is `u64::MAX`.
+#[test]
+fn test_validate_mint_entire_max_reserve() {
+    let mint = mint_tx(u64::MAX, 0, 0, u64::MAX);
+
+    assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Tests that a mint destroying tokens is rejected instead of wrapping around.
+///
+/// With unchecked arithmetic `5 - 10` wraps to `u64::MAX - 4`, which equals the
+/// supply decrease below, so a release build would have accepted this mint.
+#[test]
+fn test_validate_mint_underflow() {
+    let mint = mint_tx(u64::MAX, 10, 4, 5);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintUnderflow {
+            input: 10,
+            output: 5
+        })
+    );
+    assert_eq!(
+        validate(&app(NFT), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintUnderflow {
+            input: 10,
+            output: 5
+        })
+    );
+}
+
+/// Tests that a supply increase near `u64::MAX` is rejected.
+#[test]
+fn test_validate_supply_increased_near_max() {
+    let mint = mint_tx(u64::MAX - 1, 0, u64::MAX, 0);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::SupplyIncreased {
+            incoming: u64::MAX - 1,
+            outgoing: u64::MAX
+        })
+    );
+}
+
+/// Tests that minting on top of existing tokens cannot overflow the output total.
+#[test]
+fn test_validate_mint_output_overflow() {
+    let mint = mint_tx(u64::MAX, 1, 0, u64::MAX);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintAmountMismatch {
+            minted: u64::MAX - 1,
+            supply_decrease: u64::MAX
+        })
+    );
+}
+
+/// Tests that output token amounts summing past `u64::MAX` are rejected.
+///
+/// With unchecked arithmetic the outputs would wrap to a total of 1, matching
+/// the supply decrease.
+#[test]
+fn test_validate_output_token_sum_overflow() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let mint = tx(
+        vec![(utxo(1), charms(&[(&nft_app, nft("MAX", u64::MAX))]))],
+        vec![
+            charms(&[(&token_app, tokens(u64::MAX))]),
+            charms(&[(&token_app, tokens(2))]),
+            charms(&[(&nft_app, nft("MAX", u64::MAX - 1))]),
+        ],
+    );
+
+    assert_eq!(
+        validate(&token_app, &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenAmountOverflow)
+    );
+    assert_eq!(
+        validate(&nft_app, &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenAmountOverflow)
+    );
+}
+
+/// Tests that input token amounts summing past `u64::MAX` are rejected.
+#[test]
+fn test_validate_input_token_sum_overflow() {
+    let token_app = app(TOKEN);
+    let send = tx(
+        vec![
+            (utxo(1), charms(&[(&token_app, tokens(u64::MAX))])),
+            (utxo(2), charms(&[(&token_app, tokens(u64::MAX))])),
+        ],
+        vec![charms(&[(&token_app, tokens(u64::MAX - 1))])],
+    );
+
+    assert_eq!(
+        validate(&token_app, &send, &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenAmountOverflow)
+    );
+}
+
+/// Tests that a retiring burn overflowing the `burned` counter is rejected.
+#[test]
+fn test_validate_burned_counter_overflow() {
+    let nft_in = NftContent {
+        ticker: "MAX".to_string(),
+        remaining: 0,
+        burned: u64::MAX,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        validate(&app(TOKEN), &supply_tx(&nft_in, 1, &nft_in, 0), &Data::empty(), &Data::empty()),
+        Err(ContractError::SupplyOverflow)
+    );
+}
+
+/// Builds NFT content that tracks `max_supply` with `minted` tokens already minted.
+fn tracked_nft(max_supply: u64, minted: u64) -> NftContent {
+    NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: max_supply - minted,
+        max_supply: Some(max_supply),
+        minted,
+        ..Default::default()
+    }
+}
+
+/// Tests that the original layout deserializes without supply tracking.
+#[test]
+fn test_nft_content_legacy_layout_untracked() {
+    let content: NftContent = nft("MY-TOKEN", 30_580).value().unwrap();
+
+    assert_eq!(content.max_supply, None);
+    assert_eq!(content.minted, 0);
+}
+
+/// Tests that an NFT tracking its supply can be minted only with a consistent invariant.
+#[test]
+fn test_validate_mint_nft_supply_invariant() {
+    let nft_app = app(NFT);
+    let ins = vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())];
+    let x = Data::empty();
+
+    let valid = tx(ins.clone(), vec![charms(&[(&nft_app, Data::from(&tracked_nft(100_000, 0)))])]);
+    assert_eq!(validate(&nft_app, &valid, &x, &witness()), Ok(()));
+
+    let inconsistent = NftContent {
+        max_supply: Some(1_000_000),
+        ..tracked_nft(100_000, 0)
+    };
+    let invalid = tx(ins, vec![charms(&[(&nft_app, Data::from(&inconsistent))])]);
+    assert_eq!(
+        validate(&nft_app, &invalid, &x, &witness()),
+        Err(ContractError::SupplyInvariantViolated {
+            max_supply: 1_000_000,
+            minted: 0,
+            remaining: 100_000
+        })
+    );
+}
+
+/// Tests that minting from a tracked NFT grows `minted` by the minted amount.
+#[test]
+fn test_validate_mint_updates_minted_counter() {
+    let mint = supply_tx(&tracked_nft(100_000, 0), 0, &tracked_nft(100_000, 69_420), 69_420);
+
+    assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()), Ok(()));
+
+    let stale = NftContent {
+        minted: 0,
+        ..tracked_nft(100_000, 69_420)
+    };
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &supply_tx(&tracked_nft(100_000, 0), 0, &stale, 69_420),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::MintedCounterMismatch {
+            expected: 69_420,
+            actual: 0
+        })
+    );
+}
+
+/// Tests that a mint cannot change the recorded `max_supply`.
+#[test]
+fn test_validate_mint_cannot_change_max_supply() {
+    let raised = NftContent {
+        max_supply: Some(200_000),
+        remaining: 130_580,
+        ..tracked_nft(100_000, 69_420)
+    };
+
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&tracked_nft(100_000, 0), 0, &raised, 69_420),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::SupplyIncreased {
+            incoming: 100_000,
+            outgoing: 130_580
+        })
+    );
+
+    let raised = NftContent {
+        max_supply: Some(200_000),
+        ..tracked_nft(100_000, 69_420)
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&tracked_nft(100_000, 0), 0, &raised, 69_420),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Tests that legacy NFTs keep minting without touching `minted`.
+#[test]
+fn test_validate_legacy_mint_keeps_minted_counter() {
+    let counted = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 30_580,
+        minted: 69_420,
+        ..Default::default()
+    };
+    let mint = supply_tx(&nft("MY-TOKEN", 100_000).value().unwrap(), 0, &counted, 69_420);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintedCounterMismatch {
+            expected: 0,
+            actual: 69_420
+        })
+    );
+}
+
+/// Tests that recycled burns on a tracked NFT move tokens from `minted` back to `remaining`.
+#[test]
+fn test_validate_recycle_burn_updates_minted_counter() {
+    let nft_in = NftContent {
+        recycle_burns: true,
+        ..tracked_nft(100_000, 69_420)
+    };
+    let nft_out = NftContent {
+        recycle_burns: true,
+        ..tracked_nft(100_000, 69_000)
+    };
+
+    let burn = supply_tx(&nft_in, 69_420, &nft_out, 69_000);
+    assert_eq!(validate(&app(TOKEN), &burn, &Data::empty(), &Data::empty()), Ok(()));
+
+    let stale = NftContent {
+        minted: 69_420,
+        ..nft_out
+    };
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &supply_tx(&nft_in, 69_420, &stale, 69_000),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::MintedCounterMismatch {
+            expected: 69_000,
+            actual: 69_420
+        })
+    );
+}
+
+/// Builds NFT content carrying the full set of token metadata.
+fn nft_with_metadata() -> NftContent {
+    NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 100_000,
+        decimals: Some(8),
+        name: Some("My Token".to_string()),
+        image_hash: Some(hash("icon.png")),
+        uri: Some("https://example.com/my-token.json".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Builds an NFT mint transaction creating an NFT with `content`.
+fn mint_nft_tx(content: &NftContent) -> Transaction {
+    tx(
+        vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())],
+        vec![charms(&[(&app(NFT), Data::from(content))])],
+    )
+}
+
+/// Tests that token metadata round-trips through `Data`.
+#[test]
+fn test_nft_content_metadata_serialization() {
+    let content = nft_with_metadata();
+
+    let deserialized: NftContent = Data::from(&content).value().unwrap();
+
+    assert_eq!(deserialized, content);
+}
+
+/// Tests that an NFT with well-formed metadata, including the limits, can be minted.
+#[test]
+fn test_validate_mint_nft_with_metadata() {
+    let x = Data::empty();
+    assert_eq!(validate(&app(NFT), &mint_nft_tx(&nft_with_metadata()), &x, &witness()), Ok(()));
+
+    let at_limits = NftContent {
+        decimals: Some(MAX_DECIMALS),
+        name: Some("N".repeat(MAX_NAME_LEN)),
+        uri: Some("u".repeat(MAX_URI_LEN)),
+        ..nft_with_metadata()
+    };
+    assert_eq!(validate(&app(NFT), &mint_nft_tx(&at_limits), &x, &witness()), Ok(()));
+}
+
+/// Tests that metadata beyond its limits is rejected at NFT mint.
+#[test]
+fn test_validate_mint_nft_metadata_limits() {
+    let x = Data::empty();
+
+    let decimals = NftContent {
+        decimals: Some(19),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&decimals), &x, &witness()),
+        Err(ContractError::TooManyDecimals(19))
+    );
+
+    let name = NftContent {
+        name: Some("N".repeat(MAX_NAME_LEN + 1)),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&name), &x, &witness()),
+        Err(ContractError::NameTooLong(MAX_NAME_LEN + 1))
+    );
+
+    let uri = NftContent {
+        uri: Some("u".repeat(MAX_URI_LEN + 1)),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&uri), &x, &witness()),
+        Err(ContractError::UriTooLong(MAX_URI_LEN + 1))
+    );
+}
+
+/// Tests that metadata cannot change once the NFT is minted.
+#[test]
+fn test_validate_metadata_is_immutable() {
+    let nft_in = nft_with_metadata();
+    let renamed = NftContent {
+        name: Some("Other Token".to_string()),
+        ..nft_in.clone()
+    };
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 1, &renamed, 1), &Data::empty(), &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+
+    let redecimated = NftContent {
+        remaining: 90_000,
+        decimals: Some(2),
+        ..nft_in.clone()
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&nft_in, 0, &redecimated, 10_000),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Tests tickers that follow the ticker grammar.
+#[test]
+fn test_check_ticker_valid() {
+    for ticker in [
+        "MY-TOKEN",
+        "A",
+        "BTC2",
+        "X-1-Y",
+        &"Z".repeat(MAX_TICKER_LEN),
+    ] {
+        assert_eq!(check_ticker(ticker), Ok(()), "{ticker} should be valid");
+    }
+}
+
+/// Tests that empty and overlong tickers are rejected.
+#[test]
+fn test_check_ticker_length() {
+    assert_eq!(check_ticker(""), Err(ContractError::InvalidTickerLength(0)));
+    assert_eq!(
+        check_ticker(&"A".repeat(MAX_TICKER_LEN + 1)),
+        Err(ContractError::InvalidTickerLength(MAX_TICKER_LEN + 1))
+    );
+}
+
+/// Tests that characters outside the ticker grammar are rejected.
+#[test]
+fn test_check_ticker_chars() {
+    assert_eq!(check_ticker("my-token"), Err(ContractError::InvalidTickerChar('m')));
+    assert_eq!(check_ticker("MY TOKEN"), Err(ContractError::InvalidTickerChar(' ')));
+    assert_eq!(check_ticker("MY_TOKEN"), Err(ContractError::InvalidTickerChar('_')));
+    assert_eq!(check_ticker("TOKÉN"), Err(ContractError::InvalidTickerChar('É')));
+}
+
+/// Tests that NFTs with tickers like `test_nft_content_empty_ticker` and
+/// `test_nft_content_long_ticker` cannot be minted.
+#[test]
+fn test_validate_mint_nft_invalid_ticker() {
+    let x = Data::empty();
+
+    let empty = NftContent {
+        ticker: String::new(),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&empty), &x, &witness()),
+        Err(ContractError::InvalidTickerLength(0))
+    );
+
+    let long = NftContent {
+        ticker: "A".repeat(1000),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&long), &x, &witness()),
+        Err(ContractError::InvalidTickerLength(1000))
+    );
+
+    let lowercase = NftContent {
+        ticker: "my-token".to_string(),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&lowercase), &x, &witness()),
+        Err(ContractError::InvalidTickerChar('m'))
+    );
+}
+
+/// Builds the signing key of a mint authority from a seed byte.
+fn authority_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+/// Returns the x-only public key of `key` as stored in `NftContent::mint_authority`.
+fn authority(key: &SigningKey) -> B32 {
+    B32(key.verifying_key().to_bytes().into())
+}
+
+/// Signs the mint message of `mint` with `key`, as a hex-encoded signature.
+fn sign_mint(key: &SigningKey, mint: &Transaction) -> String {
+    let message = mint_message(&app(TOKEN), mint).unwrap();
+    hex::encode(key.sign_raw(&message.0, &[0; 32]).unwrap().to_bytes())
+}
+
+fn mint_witness(signature: String) -> Data {
+    Data::from(&MintWitness {
+        signature,
+        ..Default::default()
+    })
+}
+
+/// Builds a mint of 69,420 tokens from an NFT with the given mint authority.
+fn authorized_mint_tx(mint_authority: Option<B32>) -> Transaction {
+    let nft_in = NftContent {
+        mint_authority: mint_authority.clone(),
+        ..tracked_nft(100_000, 0)
+    };
+    let nft_out = NftContent {
+        mint_authority,
+        ..tracked_nft(100_000, 69_420)
+    };
+    Transaction {
+        coin_outs: Some(coin_outs(&[b"minter", b"reserve"])),
+        ..supply_tx(&nft_in, 0, &nft_out, 69_420)
+    }
+}
+
+/// Tests that a mint signed by the mint authority is accepted by both apps.
+#[test]
+fn test_validate_mint_with_authority_signature() {
+    let key = authority_key(1);
+    let mint = authorized_mint_tx(Some(authority(&key)));
+    let w = mint_witness(sign_mint(&key, &mint));
+
+    assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &w), Ok(()));
+    assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &w), Ok(()));
+}
+
+/// Tests that a mint from an NFT with a mint authority needs its signature.
+#[test]
+fn test_validate_mint_missing_authority_signature() {
+    let mint = authorized_mint_tx(Some(authority(&authority_key(1))));
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MissingMintSignature)
+    );
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &mint_witness("zz".to_string())),
+        Err(ContractError::InvalidSignatureEncoding)
+    );
+}
+
+/// Tests that signatures by another key or over another mint are rejected.
+#[test]
+fn test_validate_mint_wrong_authority_signature() {
+    let key = authority_key(1);
+    let mint = authorized_mint_tx(Some(authority(&key)));
+
+    let other_key = mint_witness(sign_mint(&authority_key(2), &mint));
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &other_key),
+        Err(ContractError::InvalidMintSignature)
+    );
+
+    let mut reordered = mint.clone();
+    reordered
+        .outs
+        .insert(0, charms(&[(&app(TOKEN), tokens(0))]));
+    reordered.coin_outs = Some(coin_outs(&[b"minter", b"minter", b"reserve"]));
+    let stale = mint_witness(sign_mint(&key, &mint));
+    assert_eq!(
+        validate(&app(TOKEN), &reordered, &Data::empty(), &stale),
+        Err(ContractError::InvalidMintSignature)
+    );
+
+    let redirected = Transaction {
+        coin_outs: Some(coin_outs(&[b"mallory", b"reserve"])),
+        ..mint.clone()
+    };
+    assert_eq!(
+        validate(&app(TOKEN), &redirected, &Data::empty(), &stale),
+        Err(ContractError::InvalidMintSignature)
+    );
+
+    let undirected = Transaction {
+        coin_outs: None,
+        ..mint
+    };
+    assert_eq!(
+        validate(&app(TOKEN), &undirected, &Data::empty(), &stale),
+        Err(ContractError::MissingDestination(0))
+    );
+}
+
+/// Tests that NFTs without a mint authority mint without a signature.
+#[test]
+fn test_validate_mint_without_authority() {
+    let mint = authorized_mint_tx(None);
+
+    assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Tests that the mint authority must be a valid key and cannot be changed.
+#[test]
+fn test_validate_mint_authority_key() {
+    let invalid = NftContent {
+        mint_authority: Some(B32([0xff; 32])),
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&invalid), &Data::empty(), &witness()),
+        Err(ContractError::InvalidMintAuthority)
+    );
+
+    let nft_in = NftContent {
+        mint_authority: Some(authority(&authority_key(1))),
+        ..nft_with_metadata()
+    };
+    let nft_out = NftContent {
+        mint_authority: None,
+        ..nft_in.clone()
+    };
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 1, &nft_out, 1), &Data::empty(), &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Builds a mint of 69,420 tokens from an NFT with a `threshold`-of-3 mint multisig
+/// over the keys seeded 1, 2 and 3.
+fn multisig_mint_tx(threshold: u32) -> Transaction {
+    let mint_authorities: Vec<B32> = (1..=3)
+        .map(|seed| authority(&authority_key(seed)))
+        .collect();
+    let nft_in = NftContent {
+        mint_authorities: mint_authorities.clone(),
+        mint_threshold: threshold,
+        ..tracked_nft(100_000, 0)
+    };
+    let nft_out = NftContent {
+        mint_authorities,
+        mint_threshold: threshold,
+        ..tracked_nft(100_000, 69_420)
+    };
+    Transaction {
+        coin_outs: Some(coin_outs(&[b"minter", b"reserve"])),
+        ..supply_tx(&nft_in, 0, &nft_out, 69_420)
+    }
+}
+
+/// Builds a multisig witness with signatures of `mint` by the keys seeded `seeds`.
+fn multisig_witness(mint: &Transaction, seeds: &[u8]) -> Data {
+    Data::from(&MintWitness {
+        signatures: seeds
+            .iter()
+            .map(|&seed| MultisigSignature {
+                signer: authority(&authority_key(seed)),
+                signature: sign_mint(&authority_key(seed), mint),
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// Tests that a 2-of-3 multisig mint is accepted with any two signers.
+#[test]
+fn test_validate_multisig_mint() {
+    let mint = multisig_mint_tx(2);
+
+    for seeds in [[1, 2], [2, 3], [3, 1]] {
+        let w = multisig_witness(&mint, &seeds);
+        assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &w), Ok(()));
+        assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &w), Ok(()));
+    }
+    let w = multisig_witness(&mint, &[1, 2, 3]);
+    assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &w), Ok(()));
+}
+
+/// Tests that a multisig mint with too few signatures is rejected.
+#[test]
+fn test_validate_multisig_threshold_not_met() {
+    let mint = multisig_mint_tx(2);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &multisig_witness(&mint, &[1])),
+        Err(ContractError::ThresholdNotMet {
+            threshold: 2,
+            signers: 1
+        })
+    );
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MissingMintSignature)
+    );
+}
+
+/// Tests that the same signer cannot be counted twice towards the threshold.
+#[test]
+fn test_validate_multisig_duplicate_signer() {
+    let mint = multisig_mint_tx(2);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &multisig_witness(&mint, &[1, 1])),
+        Err(ContractError::DuplicateSigner)
+    );
+}
+
+/// Tests that signatures by keys outside the multisig are rejected.
+#[test]
+fn test_validate_multisig_unknown_signer() {
+    let mint = multisig_mint_tx(2);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &multisig_witness(&mint, &[1, 4])),
+        Err(ContractError::UnknownSigner)
+    );
+}
+
+/// Tests that multisig signatures over another mint message are rejected.
+#[test]
+fn test_validate_multisig_wrong_message() {
+    let mint = multisig_mint_tx(2);
+    let mut other = mint.clone();
+    other.outs.insert(0, charms(&[(&app(TOKEN), tokens(0))]));
+    other.coin_outs = Some(coin_outs(&[b"minter", b"minter", b"reserve"]));
+
+    assert_eq!(
+        validate(&app(TOKEN), &other, &Data::empty(), &multisig_witness(&mint, &[1, 2])),
+        Err(ContractError::InvalidMintSignature)
+    );
+}
+
+/// Tests that the multisig configuration is checked at NFT mint.
+#[test]
+fn test_validate_mint_nft_multisig_config() {
+    let keys: Vec<B32> = (1..=3)
+        .map(|seed| authority(&authority_key(seed)))
+        .collect();
+    let x = Data::empty();
+
+    for threshold in [0, 4] {
+        let content = NftContent {
+            mint_authorities: keys.clone(),
+            mint_threshold: threshold,
+            ..nft_with_metadata()
+        };
+        assert_eq!(
+            validate(&app(NFT), &mint_nft_tx(&content), &x, &witness()),
+            Err(ContractError::InvalidMintThreshold {
+                threshold,
+                authorities: 3
+            })
+        );
+    }
+
+    let no_keys = NftContent {
+        mint_threshold: 1,
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&no_keys), &x, &witness()),
+        Err(ContractError::InvalidMintThreshold {
+            threshold: 1,
+            authorities: 0
+        })
+    );
+
+    let duplicated = NftContent {
+        mint_authorities: vec![keys[0].clone(), keys[0].clone()],
+        mint_threshold: 2,
+        ..nft_with_metadata()
+    };
+    assert_eq!(
+        validate(&app(NFT), &mint_nft_tx(&duplicated), &x, &witness()),
+        Err(ContractError::DuplicateMintAuthority)
+    );
+
+    let valid = NftContent {
+        mint_authorities: keys,
+        mint_threshold: 2,
+        ..nft_with_metadata()
+    };
+    assert_eq!(validate(&app(NFT), &mint_nft_tx(&valid), &x, &witness()), Ok(()));
+}
+
+/// Tests that the NFT holder can finalize the NFT without changing anything else.
+#[test]
+fn test_validate_finalize_nft() {
+    let nft_in = tracked_nft(100_000, 69_420);
+    let finalized = NftContent {
+        finalized: true,
+        ..nft_in.clone()
+    };
+
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 0, &finalized, 0), &Data::empty(), &Data::empty()),
+        Ok(())
+    );
+
+    // a live copy next to the finalized NFT would keep minting.
+    let mut forked = supply_tx(&nft_in, 0, &finalized, 0);
+    forked
+        .outs
+        .push(charms(&[(&app(NFT), Data::from(&nft_in))]));
+    assert_eq!(
+        validate(&app(NFT), &forked, &Data::empty(), &Data::empty()),
+        Err(ContractError::DuplicateOutputNft(2))
+    );
+
+    let drained = NftContent {
+        remaining: 0,
+        ..finalized
+    };
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 0, &drained, 0), &Data::empty(), &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Tests that a finalized NFT can no longer mint tokens.
+#[test]
+fn test_validate_finalized_nft_cannot_mint() {
+    let nft_in = NftContent {
+        finalized: true,
+        ..tracked_nft(100_000, 0)
+    };
+    let nft_out = NftContent {
+        finalized: true,
+        ..tracked_nft(100_000, 69_420)
+    };
+    let mint = supply_tx(&nft_in, 0, &nft_out, 69_420);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::SupplyFinalized)
+    );
+    assert_eq!(
+        validate(&app(NFT), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::SupplyFinalized)
+    );
+}
+
+/// Tests that a finalized NFT can be moved unchanged but never unfinalized.
+#[test]
+fn test_validate_finalized_nft_transfers() {
+    let finalized = NftContent {
+        finalized: true,
+        ..tracked_nft(100_000, 69_420)
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&finalized, 10, &finalized, 10),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Ok(())
+    );
+
+    let unfinalized = NftContent {
+        finalized: false,
+        ..finalized.clone()
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&finalized, 10, &unfinalized, 10),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::SupplyFinalized)
+    );
+}
+
+/// Tests that tokens of a finalized NFT can still be burned.
+#[test]
+fn test_validate_finalized_nft_burn() {
+    let nft_in = NftContent {
+        finalized: true,
+        ..tracked_nft(100_000, 69_420)
+    };
+    let nft_out = NftContent {
+        burned: 420,
+        ..nft_in.clone()
+    };
+    let burn = supply_tx(&nft_in, 69_420, &nft_out, 69_000);
+
+    assert_eq!(validate(&app(TOKEN), &burn, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(validate(&app(NFT), &burn, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Builds a mint of `amount` tokens from a tracked NFT capped at `cap` tokens per transaction.
+fn capped_mint_tx(cap: u64, amount: u64) -> Transaction {
+    let nft_in = NftContent {
+        max_mint_per_tx: Some(cap),
+        ..tracked_nft(100_000, 0)
+    };
+    let nft_out = NftContent {
+        max_mint_per_tx: Some(cap),
+        ..tracked_nft(100_000, amount)
+    };
+    supply_tx(&nft_in, 0, &nft_out, amount)
+}
+
+/// Tests that mints up to the per-transaction cap are accepted.
+#[test]
+fn test_validate_mint_within_cap() {
+    for amount in [1, 9_999, 10_000] {
+        let mint = capped_mint_tx(10_000, amount);
+        assert_eq!(validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()), Ok(()));
+        assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &Data::empty()), Ok(()));
+    }
+}
+
+/// Tests that a mint like `spells/mint-token.yaml` exceeding the cap is rejected.
+#[test]
+fn test_validate_mint_cap_exceeded() {
+    let mint = capped_mint_tx(10_000, 69_420);
+
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintCapExceeded {
+            minted: 69_420,
+            cap: 10_000
+        })
+    );
+    assert_eq!(
+        validate(&app(NFT), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MintCapExceeded {
+            minted: 69_420,
+            cap: 10_000
+        })
+    );
+}
+
+/// Tests that a mint cannot lift the per-transaction cap.
+#[test]
+fn test_validate_mint_cap_is_immutable() {
+    let nft_in = NftContent {
+        max_mint_per_tx: Some(10_000),
+        ..tracked_nft(100_000, 0)
+    };
+    let uncapped = tracked_nft(100_000, 10_000);
+
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&nft_in, 0, &uncapped, 10_000),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+}
+
+/// Builds a schedule releasing 1000 tokens from height 100, halving every 10 blocks.
+const fn emission() -> EmissionSchedule {
+    EmissionSchedule {
+        start_height: 100,
+        epoch_allowance: 1_000,
+        halving_interval: 10,
+    }
+}
+
+/// Builds a transaction minting `amount` more tokens after `minted` under [`emission`].
+fn emission_mint_tx(minted: u64, amount: u64) -> Transaction {
+    let nft_in = NftContent {
+        emission: Some(emission()),
+        ..tracked_nft(100_000, minted)
+    };
+    let nft_out = NftContent {
+        emission: Some(emission()),
+        ..tracked_nft(100_000, minted + amount)
+    };
+    supply_tx(&nft_in, 0, &nft_out, amount)
+}
+
+/// Tests the cumulative allowance at the epoch boundaries.
+#[test]
+fn test_emission_allowance_at() {
+    let schedule = emission();
+
+    assert_eq!(schedule.allowance_at(0), 0);
+    assert_eq!(schedule.allowance_at(99), 0);
+    assert_eq!(schedule.allowance_at(100), 1_000);
+    assert_eq!(schedule.allowance_at(109), 1_000);
+    assert_eq!(schedule.allowance_at(110), 1_500);
+    assert_eq!(schedule.allowance_at(120), 1_750);
+    assert_eq!(schedule.allowance_at(u64::MAX), 1_994);
+}
+
+/// Tests that mints within the released allowance are accepted.
+#[test]
+fn test_validate_mint_within_emission() {
+    let height = mint_at(110);
+
+    for (minted, amount) in [(0, 1_000), (0, 1_500), (1_000, 500)] {
+        let mint = timelocked(emission_mint_tx(minted, amount), 110);
+        assert_eq!(validate(&app(TOKEN), &mint, &height, &Data::empty()), Ok(()));
+        assert_eq!(validate(&app(NFT), &mint, &height, &Data::empty()), Ok(()));
+    }
+}
+
+/// Tests that mints ahead of the emission schedule are rejected.
+#[test]
+fn test_validate_mint_exceeds_emission() {
+    let mint = timelocked(emission_mint_tx(1_000, 501), 110);
+    let expected = Err(ContractError::EmissionExceeded {
+        minted: 1_501,
+        allowed: 1_500,
+    });
+
+    assert_eq!(validate(&app(TOKEN), &mint, &mint_at(110), &Data::empty()), expected);
+    assert_eq!(validate(&app(NFT), &mint, &mint_at(110), &Data::empty()), expected);
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &timelocked(emission_mint_tx(0, 1), 99),
+            &mint_at(99),
+            &Data::empty()
+        ),
+        Err(ContractError::EmissionExceeded {
+            minted: 1,
+            allowed: 0
+        })
+    );
+}
+
+/// Tests that a mint cannot declare a block height its timelocked input does not prove.
+#[test]
+fn test_validate_mint_emission_unproven_height() {
+    let mint = emission_mint_tx(0, 1_500);
+    let at_110 = ContractInput::Mint { height: Some(110) };
+
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &timelocked(mint.clone(), 100),
+            &declare_proven(at_110, 100),
+            &Data::empty()
+        ),
+        Err(ContractError::HeightNotProven {
+            height: 110,
+            proven: 100
+        })
+    );
+    assert_eq!(
+        validate(&app(TOKEN), &timelocked(mint.clone(), 110), &declare(at_110), &Data::empty()),
+        Err(ContractError::MissingHeightProof)
+    );
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &timelocked(mint.clone(), 100),
+            &declare_proven(at_110, 110),
+            &Data::empty()
+        ),
+        Err(ContractError::InvalidHeightProof)
+    );
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &declare_proven(at_110, 110), &Data::empty()),
+        Err(ContractError::InvalidHeightProof)
+    );
+
+    let timestamp = LOCKTIME_THRESHOLD + 110;
+    assert_eq!(
+        validate(
+            &app(TOKEN),
+            &timelocked(mint, timestamp),
+            &declare_proven(at_110, timestamp),
+            &Data::empty()
+        ),
+        Err(ContractError::InvalidHeightProof)
+    );
+}
+
+/// Tests that block height locks round-trip through their witness scripts,
+/// whatever the length of the pushed number.
+#[test]
+fn test_proven_height_round_trip() {
+    for height in [
+        0,
+        1,
+        16,
+        17,
+        127,
+        128,
+        255,
+        256,
+        32_767,
+        32_768,
+        880_000,
+        LOCKTIME_THRESHOLD - 1,
+    ] {
+        let proof = HeightProof {
+            input: 0,
+            witness_script: hex::encode(lock_script(height)),
+        };
+        let tx = timelocked(tx(vec![], vec![]), height);
+        assert_eq!(proven_height(&tx, &proof), Ok(height));
+    }
+    assert_eq!(lock_script(128)[..3], [0x02, 0x80, 0x00]);
+    assert_eq!(lock_script(16)[0], 0x60);
+
+    let unlocked = HeightProof {
+        input: 0,
+        witness_script: "51".to_string(),
+    };
+    let tx = Transaction {
+        coin_ins: Some(vec![NativeOutput {
+            amount: 1000,
+            dest: p2wsh_script_pubkey(&[0x51]),
+        }]),
+        ..tx(vec![(utxo(0xff), Charms::new())], vec![])
+    };
+    assert_eq!(proven_height(&tx, &unlocked), Err(ContractError::InvalidHeightProof));
+}
+
+/// Tests that a mint under an emission schedule must declare the block height.
+#[test]
+fn test_validate_mint_emission_missing_height() {
+    let mint = emission_mint_tx(0, 1);
+
+    for x in [Data::empty(), declare(ContractInput::Mint { height: None })] {
+        assert_eq!(
+            validate(&app(TOKEN), &mint, &x, &Data::empty()),
+            Err(ContractError::MissingBlockHeight)
+        );
+    }
+}
+
+/// Tests that an emission schedule needs a halving interval, a tracked supply and
+/// no airdrop.
+#[test]
+fn test_validate_nft_mint_invalid_emission() {
+    let no_interval = NftContent {
+        emission: Some(EmissionSchedule {
+            halving_interval: 0,
+            ..emission()
+        }),
+        ..tracked_nft(100_000, 0)
+    };
+    let untracked = NftContent {
+        ticker: "MY-TOKEN".to_string(),
+        remaining: 100_000,
+        emission: Some(emission()),
+        ..Default::default()
+    };
+    let airdropped = NftContent {
+        emission: Some(emission()),
+        airdrop: Some(Airdrop::default()),
+        ..tracked_nft(100_000, 0)
+    };
+
+    for content in [no_interval, untracked, airdropped] {
+        assert_eq!(
+            validate(&app(NFT), &mint_nft_tx(&content), &Data::empty(), &witness()),
+            Err(ContractError::InvalidEmissionSchedule)
+        );
+    }
+}
+
+/// Builds the app of the collection NFT at `index`.
+fn collection_app(index: u32) -> App {
+    App {
+        identity: collection_identity(MINT_UTXO, index),
+        ..app(NFT)
+    }
+}
+
+/// Builds a transaction minting the collection NFTs at `indices` out of `size`.
+fn collection_tx(indices: &[u32], size: u32) -> Transaction {
+    let outs = indices
+        .iter()
+        .map(|&index| {
+            let content = NftContent {
+                ticker: "MY-NFT".to_string(),
+                collection: Some(CollectionItem { index, size }),
+                ..Default::default()
+            };
+            charms(&[(&collection_app(index), Data::from(&content))])
+        })
+        .collect();
+    tx(vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())], outs)
+}
+
+/// Tests that every NFT of a collection minted from one UTXO is accepted.
+#[test]
+fn test_validate_mint_collection() {
+    let mint = collection_tx(&[2, 0, 1], 3);
+
+    for index in 0..3 {
+        assert_ne!(collection_app(index).identity, hash(MINT_UTXO));
+        assert_eq!(validate(&collection_app(index), &mint, &Data::empty(), &witness()), Ok(()));
+    }
+    assert_eq!(
+        validate(&app(NFT), &mint, &Data::empty(), &witness()),
+        Err(ContractError::NftCountMismatch(0))
+    );
+}
+
+/// Tests that a collection with a gap is rejected.
+#[test]
+fn test_validate_mint_collection_gap() {
+    let mint = collection_tx(&[0, 2], 3);
+
+    assert_eq!(
+        validate(&collection_app(0), &mint, &Data::empty(), &witness()),
+        Err(ContractError::MissingCollectionItem(1))
+    );
+}
+
+/// Tests that a collection minting an index twice is rejected.
+#[test]
+fn test_validate_mint_collection_duplicate() {
+    let mint = collection_tx(&[0, 1, 1, 2], 3);
+
+    assert_eq!(
+        validate(&collection_app(0), &mint, &Data::empty(), &witness()),
+        Err(ContractError::DuplicateCollectionItem(1))
+    );
+    assert_eq!(
+        validate(&collection_app(1), &mint, &Data::empty(), &witness()),
+        Err(ContractError::NftCountMismatch(2))
+    );
+}
+
+/// Tests that collection items must commit to a consistent position.
+#[test]
+fn test_validate_mint_collection_position() {
+    let out_of_range = collection_tx(&[0, 1, 2, 3], 3);
+    assert_eq!(
+        validate(&collection_app(3), &out_of_range, &Data::empty(), &witness()),
+        Err(ContractError::InvalidCollectionIndex { index: 3, size: 3 })
+    );
+
+    let mut resized = collection_tx(&[0, 1, 2], 3);
+    resized.outs[1] = collection_tx(&[1], 4).outs.remove(0);
+    assert_eq!(
+        validate(&collection_app(0), &resized, &Data::empty(), &witness()),
+        Err(ContractError::CollectionItemMismatch(1))
+    );
+    assert_eq!(
+        validate(&collection_app(1), &resized, &Data::empty(), &witness()),
+        Err(ContractError::CollectionItemMismatch(0))
+    );
+}
+
+/// Builds the content of the parent collection NFT with `minted_items` children.
+fn parent_collection(minted_items: u32) -> Data {
+    Data::from(&CollectionContent {
+        collection_name: "My Collection".to_string(),
+        max_items: 3,
+        minted_items,
+    })
+}
+
+/// Builds the app of the child NFT `number` of the parent `app(NFT)`.
+fn child_app(number: u32) -> App {
+    App {
+        identity: child_identity(&app(NFT).identity, number),
+        ..app(NFT)
+    }
+}
+
+/// Builds the content of the child NFT `number` of the parent `app(NFT)`.
+fn child(number: u32) -> Data {
+    Data::from(&ChildContent {
+        parent: app(NFT).identity,
+        number,
+        name: Some(format!("Item #{number}")),
+    })
+}
+
+/// Builds a transaction spending the parent with `minted_in` children, minting
+/// the children `numbers` and recreating it with `minted_out`.
+fn children_mint_tx(minted_in: u32, numbers: &[u32], minted_out: u32) -> Transaction {
+    let mut outs = vec![charms(&[(&app(NFT), parent_collection(minted_out))])];
+    outs.extend(
+        numbers
+            .iter()
+            .map(|&number| charms(&[(&child_app(number), child(number))])),
+    );
+    tx(vec![(utxo(1), charms(&[(&app(NFT), parent_collection(minted_in))]))], outs)
+}
+
+/// Content of a child NFT that also holds the fields of a reserve.
+#[derive(Serialize)]
+struct HybridChild {
+    parent: B32,
+    number: u32,
+    ticker: String,
+    remaining: u64,
+}
+
+/// Builds the content of the child NFT `number` mixed with a reserve of `remaining`.
+fn hybrid_child(number: u32, remaining: u64) -> Data {
+    Data::from(&HybridChild {
+        parent: app(NFT).identity,
+        number,
+        ticker: "lowercase bad ticker!!".to_string(),
+        remaining,
+    })
+}
+
+/// Tests that NFT content is told apart by its fields.
+#[test]
+fn test_nft_state_discrimination() {
+    assert!(matches!(nft("MY-TOKEN", 10).value(), Ok(NftState::Reserve(_))));
+    assert!(matches!(parent_collection(0).value(), Ok(NftState::Collection(_))));
+    assert_eq!(
+        child(1).value::<NftState>().unwrap(),
+        NftState::Child(ChildContent {
+            parent: app(NFT).identity,
+            number: 1,
+            name: Some("Item #1".to_string()),
+        })
+    );
+    assert!(hybrid_child(1, 10).value::<NftState>().is_err());
+    assert!(hybrid_child(1, 10).value::<ChildContent>().is_err());
+}
+
+/// Tests that content mixing child and reserve fields is neither, so no app
+/// mints or moves it, and that no token is minted under a child identity.
+#[test]
+fn test_validate_hybrid_child_rejected() {
+    let child_token = App {
+        tag: TOKEN,
+        ..child_app(0)
+    };
+    let mut minted = children_mint_tx(0, &[0], 1);
+    minted.outs[1] = charms(&[(&child_app(0), hybrid_child(0, 10))]);
+    assert_eq!(
+        validate(&app(NFT), &minted, &Data::empty(), &Data::empty()),
+        Err(ContractError::MissingCollectionItem(0))
+    );
+    assert_eq!(
+        validate(&child_app(0), &minted, &Data::empty(), &Data::empty()),
+        Err(ContractError::WitnessNotString)
+    );
+
+    let inflated = tx(
+        vec![(utxo(1), charms(&[(&child_app(0), hybrid_child(0, 10))]))],
+        vec![
+            charms(&[(&child_app(0), hybrid_child(0, u64::MAX))]),
+            charms(&[(&child_token, tokens(1000))]),
+        ],
+    );
+    let declared = declare(ContractInput::Mint { height: None });
+    for x in [Data::empty(), declared.clone()] {
+        assert_eq!(
+            validate(&child_app(0), &inflated, &x, &Data::empty()),
+            Err(ContractError::WitnessNotString)
+        );
+    }
+    assert_eq!(
+        validate(&child_token, &inflated, &declared, &Data::empty()),
+        Err(ContractError::MissingInputNft)
+    );
+
+    let mut from_child = children_mint_tx(0, &[0], 1);
+    from_child
+        .ins
+        .push((utxo(2), charms(&[(&child_app(0), child(0))])));
+    from_child
+        .outs
+        .push(charms(&[(&child_token, tokens(1000))]));
+    assert_eq!(
+        validate(&child_token, &from_child, &declared, &Data::empty()),
+        Err(ContractError::MissingInputNft)
+    );
+}
+
+/// Tests that a parent collection NFT is minted like `spells/mint-collection-parent.yaml`.
+#[test]
+fn test_validate_mint_parent_collection() {
+    let mint = tx(
+        vec![(UtxoId::from_str(MINT_UTXO).unwrap(), Charms::new())],
+        vec![charms(&[(&app(NFT), parent_collection(0))])],
+    );
+    assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &witness()), Ok(()));
+
+    let started = tx(mint.ins, vec![charms(&[(&app(NFT), parent_collection(1))])]);
+    assert_eq!(
+        validate(&app(NFT), &started, &Data::empty(), &witness()),
+        Err(ContractError::InvalidCollection)
+    );
+}
+
+/// Tests that children are minted by spending and recreating their parent.
+#[test]
+fn test_validate_mint_children() {
+    let mint = children_mint_tx(1, &[1, 2], 3);
+
+    assert_eq!(validate(&app(NFT), &mint, &Data::empty(), &Data::empty()), Ok(()));
+    for number in [1, 2] {
+        assert_eq!(validate(&child_app(number), &mint, &Data::empty(), &Data::empty()), Ok(()));
+    }
+    let declared = declare(ContractInput::Mint { height: None });
+    assert_eq!(validate(&child_app(1), &mint, &declared, &Data::empty()), Ok(()));
+}
+
+/// Tests that a child cannot be minted without its parent or under another number.
+#[test]
+fn test_validate_mint_child_requires_parent() {
+    let orphan = tx(vec![], vec![charms(&[(&child_app(0), child(0))])]);
+    assert_eq!(
+        validate(&child_app(0), &orphan, &Data::empty(), &Data::empty()),
+        Err(ContractError::MissingParentNft)
+    );
+
+    let mut impostor = children_mint_tx(0, &[0], 1);
+    impostor.outs[1] = charms(&[(&child_app(0), child(5))]);
+    assert_eq!(
+        validate(&child_app(0), &impostor, &Data::empty(), &Data::empty()),
+        Err(ContractError::IdentityMismatch)
+    );
+}
+
+/// Tests that the parent accounts for exactly the children minted.
+#[test]
+fn test_validate_mint_children_accounting() {
+    let empty = Data::empty();
+
+    assert_eq!(
+        validate(&app(NFT), &children_mint_tx(0, &[0], 2), &empty, &empty),
+        Err(ContractError::MissingCollectionItem(1))
+    );
+    assert_eq!(
+        validate(&app(NFT), &children_mint_tx(0, &[0, 0], 1), &empty, &empty),
+        Err(ContractError::DuplicateCollectionItem(0))
+    );
+    assert_eq!(
+        validate(&app(NFT), &children_mint_tx(1, &[0, 1], 2), &empty, &empty),
+        Err(ContractError::InvalidCollectionIndex { index: 0, size: 2 })
+    );
+    assert_eq!(
+        validate(&app(NFT), &children_mint_tx(2, &[2, 3], 4), &empty, &empty),
+        Err(ContractError::CollectionFull { max_items: 3 })
+    );
+    assert_eq!(
+        validate(&child_app(1), &children_mint_tx(2, &[1], 3), &empty, &empty),
+        Err(ContractError::InvalidCollectionIndex { index: 1, size: 3 })
+    );
+}
+
+/// Tests that parents and children can be moved but not altered.
+#[test]
+fn test_validate_collection_transfer() {
+    let empty = Data::empty();
+    assert_eq!(validate(&app(NFT), &children_mint_tx(2, &[], 2), &empty, &empty), Ok(()));
+
+    let moved = tx(
+        vec![(utxo(2), charms(&[(&child_app(0), child(0))]))],
+        vec![charms(&[(&child_app(0), child(0))])],
+    );
+    assert_eq!(validate(&child_app(0), &moved, &empty, &empty), Ok(()));
+
+    let renamed = tx(moved.ins.clone(), vec![charms(&[(&child_app(0), child(1))])]);
+    assert_eq!(
+        validate(&child_app(0), &renamed, &empty, &empty),
+        Err(ContractError::NftStateChanged)
+    );
+    assert_eq!(
+        validate(&child_app(0), &tx(moved.ins, vec![]), &empty, &empty),
+        Err(ContractError::MissingOutputNft)
+    );
+}
+
+/// Builds the NFT with the given mutable metadata.
+fn described_nft(uri: &str, description: &str, metadata_locked: bool) -> NftContent {
+    NftContent {
+        uri: Some(uri.to_string()),
+        description: Some(description.to_string()),
+        metadata_locked,
+        ..nft_with_metadata()
+    }
+}
+
+/// Tests that the holder can update the URI and description, like
+/// `spells/update-metadata.yaml`.
+#[test]
+fn test_validate_update_metadata() {
+    let nft_in = described_nft("https://example.com/old.json", "Typo", false);
+    let nft_out = described_nft("https://example.com/token.json", "My token", false);
+    let update = supply_tx(&nft_in, 0, &nft_out, 0);
+
+    assert_eq!(validate(&app(NFT), &update, &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(
+        validate(&app(NFT), &update, &declare(ContractInput::UpdateMetadata), &Data::empty()),
+        Ok(())
+    );
+
+    let lock = supply_tx(
+        &nft_out,
+        0,
+        &described_nft("https://example.com/token.json", "My token", true),
+        0,
+    );
+    assert_eq!(validate(&app(NFT), &lock, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Tests that a metadata update cannot touch the frozen fields or exceed the limits.
+#[test]
+fn test_validate_update_metadata_frozen_fields() {
+    let nft_in = described_nft("https://example.com/old.json", "Typo", false);
+    let renamed = NftContent {
+        name: Some("Other".to_string()),
+        ..described_nft("https://example.com/token.json", "My token", false)
+    };
+    let declared = declare(ContractInput::UpdateMetadata);
+
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 0, &renamed, 0), &declared, &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 0, &renamed, 0), &Data::empty(), &Data::empty()),
+        Err(ContractError::NftStateChanged)
+    );
+
+    let long =
+        described_nft("https://example.com/old.json", &"x".repeat(MAX_DESCRIPTION_LEN + 1), false);
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&nft_in, 0, &long, 0), &declared, &Data::empty()),
+        Err(ContractError::DescriptionTooLong(MAX_DESCRIPTION_LEN + 1))
+    );
+}
+
+/// Tests that locked metadata can no longer be updated or unlocked.
+#[test]
+fn test_validate_update_metadata_locked() {
+    let nft_in = described_nft("https://example.com/token.json", "My token", true);
+
+    for nft_out in [
+        described_nft("https://example.com/other.json", "My token", true),
+        described_nft("https://example.com/token.json", "My token", false),
+    ] {
+        assert_eq!(
+            validate(
+                &app(NFT),
+                &supply_tx(&nft_in, 0, &nft_out, 0),
+                &Data::empty(),
+                &Data::empty()
+            ),
+            Err(ContractError::MetadataLocked)
+        );
+    }
+}
+
+/// Builds a soulbound token amount.
+fn soulbound(amount: u64) -> Data {
+    Data::from(&TokenAmount::Soulbound { soulbound: amount })
+}
+
+/// Tests that NFTs without the `transferable` field mint transferable tokens.
+#[test]
+fn test_nft_content_transferable_by_default() {
+    let content: NftContent = nft("MY-TOKEN", 10).value().unwrap();
+
+    assert!(content.transferable);
+    assert!(NftContent::default().transferable);
+    assert_eq!(tokens(5).value::<TokenAmount>().unwrap(), TokenAmount::Transferable(5));
+    assert!(soulbound(5).value::<TokenAmount>().unwrap().is_soulbound());
+}
+
+/// Tests that a non-transferable NFT mints soulbound tokens, like
+/// `spells/mint-soulbound.yaml`.
+#[test]
+fn test_validate_mint_soulbound() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let badge = |remaining: u64| NftContent {
+        transferable: false,
+        ..tracked_nft(100, 100 - remaining)
+    };
+    let mint = |amount: Data| {
+        tx(
+            vec![(utxo(1), charms(&[(&nft_app, Data::from(&badge(100)))]))],
+            vec![
+                charms(&[(&token_app, amount)]),
+                charms(&[(&nft_app, Data::from(&badge(99)))]),
+            ],
+        )
+    };
+
+    assert_eq!(
+        validate(&token_app, &mint(soulbound(1)), &Data::empty(), &Data::empty()),
+        Ok(())
+    );
+    assert_eq!(validate(&nft_app, &mint(soulbound(1)), &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(
+        validate(&token_app, &mint(tokens(1)), &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenFormMismatch)
+    );
+
+    let mut forged = mint_tx(100_000, 0, 30_580, 69_420);
+    forged.outs[0] = charms(&[(&token_app, soulbound(69_420))]);
+    assert_eq!(
+        validate(&token_app, &forged, &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenFormMismatch)
+    );
+}
+
+/// Tests that soulbound tokens cannot be transferred, like
+/// `spells/transfer-soulbound.yaml`.
+#[test]
+fn test_validate_transfer_soulbound() {
+    let token_app = app(TOKEN);
+    let ins = vec![(utxo(2), charms(&[(&token_app, soulbound(1))]))];
+
+    for amount in [soulbound(1), tokens(1)] {
+        let send = tx(ins.clone(), vec![charms(&[(&token_app, amount)])]);
+        assert_eq!(
+            validate(&token_app, &send, &Data::empty(), &Data::empty()),
+            Err(ContractError::NonTransferable)
+        );
+        assert_eq!(
+            validate(&token_app, &send, &declare(ContractInput::Transfer), &Data::empty()),
+            Err(ContractError::NonTransferable)
+        );
+    }
+}
+
+/// Tests that the holder can burn soulbound tokens without the NFT, like
+/// `spells/burn-soulbound.yaml`.
+#[test]
+fn test_validate_burn_soulbound() {
+    let token_app = app(TOKEN);
+    let burn = tx(vec![(utxo(2), charms(&[(&token_app, soulbound(1))]))], vec![Charms::new()]);
+
+    assert_eq!(validate(&token_app, &burn, &Data::empty(), &Data::empty()), Ok(()));
+}
+
+/// Builds the pausable NFT with the given `paused` flag.
+fn pausable_nft(paused: bool) -> NftContent {
+    NftContent {
+        pausable: true,
+        paused,
+        ..tracked_nft(100_000, 69_420)
+    }
+}
+
+/// Builds pausable tokens.
+fn pausable(amount: u64) -> Data {
+    Data::from(&TokenAmount::Pausable { pausable: amount })
+}
+
+/// Tests that the NFT holder can pause and resume transfers, like `spells/pause.yaml`.
+#[test]
+fn test_validate_toggle_pause() {
+    for (paused_in, paused_out) in [(false, true), (true, false)] {
+        let toggle = supply_tx(&pausable_nft(paused_in), 0, &pausable_nft(paused_out), 0);
+        assert_eq!(validate(&app(NFT), &toggle, &Data::empty(), &Data::empty()), Ok(()));
+    }
+
+    let sneaky = NftContent {
+        remaining: 40_000,
+        ..pausable_nft(true)
+    };
+    assert_eq!(
+        validate(
+            &app(NFT),
+            &supply_tx(&pausable_nft(false), 0, &sneaky, 0),
+            &Data::empty(),
+            &Data::empty()
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+
+    let fixed = tracked_nft(100_000, 69_420);
+    let paused = NftContent {
+        paused: true,
+        ..fixed.clone()
+    };
+    assert_eq!(
+        validate(&app(NFT), &supply_tx(&fixed, 0, &paused, 0), &Data::empty(), &Data::empty()),
+        Err(ContractError::NotPausable)
+    );
+}
+
+/// Tests that tokens of a pausable NFT are minted in the pausable form.
+#[test]
+fn test_validate_mint_pausable_tokens() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let reserve = |minted| NftContent {
+        pausable: true,
+        ..tracked_nft(100_000, minted)
+    };
+    let mint = |minted| {
+        tx(
+            vec![(utxo(1), charms(&[(&nft_app, Data::from(&reserve(69_420)))]))],
+            vec![
+                charms(&[(&token_app, minted)]),
+                charms(&[(&nft_app, Data::from(&reserve(69_840)))]),
+            ],
+        )
+    };
+
+    assert_eq!(
+        validate(&token_app, &mint(pausable(420)), &Data::empty(), &Data::empty()),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &mint(tokens(420)), &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenFormMismatch)
+    );
+}
+
+/// Tests that pausable tokens only move in transactions spending the NFT.
+#[test]
+fn test_validate_transfer_while_paused() {
+    let token_app = app(TOKEN);
+    let nft_app = app(NFT);
+    let send = |paused| {
+        tx(
+            vec![
+                (utxo(1), charms(&[(&nft_app, Data::from(&pausable_nft(paused)))])),
+                (utxo(2), charms(&[(&token_app, pausable(420))])),
+            ],
+            vec![
+                charms(&[(&token_app, pausable(420))]),
+                charms(&[(&nft_app, Data::from(&pausable_nft(paused)))]),
+            ],
+        )
+    };
+
+    for paused in [false, true] {
+        for app in [&token_app, &nft_app] {
+            assert_eq!(validate(app, &send(paused), &Data::empty(), &Data::empty()), Ok(()));
+        }
+    }
+
+    // a reference may show the NFT from before the pause, so it approves nothing.
+    let stale = Transaction {
+        ins: vec![(utxo(2), charms(&[(&token_app, pausable(420))]))],
+        refs: vec![(utxo(1), charms(&[(&nft_app, Data::from(&pausable_nft(false)))]))],
+        outs: vec![charms(&[(&token_app, pausable(420))])],
+        ..send(false)
+    };
+    assert_eq!(
+        validate(&token_app, &stale, &Data::empty(), &Data::empty()),
+        Err(ContractError::PauseUnavailable)
+    );
+    let hidden = Transaction {
+        refs: vec![],
+        ..stale
+    };
+    assert_eq!(
+        validate(&token_app, &hidden, &declare(ContractInput::Transfer), &Data::empty()),
+        Err(ContractError::PauseUnavailable)
+    );
+    let unwrapped = Transaction {
+        outs: vec![
+            charms(&[(&token_app, tokens(420))]),
+            charms(&[(&nft_app, Data::from(&pausable_nft(false)))]),
+        ],
+        ..send(false)
+    };
+    assert_eq!(
+        validate(&token_app, &unwrapped, &Data::empty(), &Data::empty()),
+        Err(ContractError::TokenFormMismatch)
+    );
+
+    // tokens of other NFTs only honor the pause when it is referenced.
+    let advisory = Transaction {
+        refs: vec![(utxo(1), charms(&[(&nft_app, Data::from(&pausable_nft(true)))]))],
+        ..tx(
+            vec![(utxo(2), charms(&[(&token_app, tokens(420))]))],
+            vec![charms(&[(&token_app, tokens(420))])],
+        )
+    };
+    assert_eq!(
+        validate(&token_app, &advisory, &Data::empty(), &Data::empty()),
+        Err(ContractError::TransfersPaused)
+    );
+}
+
+/// Tests that pausable and allowlisted NFTs are never burned, stranding their tokens.
+#[test]
+fn test_validate_burn_required_nft() {
+    let nft_app = app(NFT);
+    let allowlisted = NftContent {
+        allowlist_root: Some(B32([7; 32])),
+        ..tracked_nft(100_000, 100_000)
+    };
+    for content in [pausable_nft(false), allowlisted] {
+        let content = NftContent {
+            burnable: true,
+            ..content
+        };
+        let burn = tx(vec![(utxo(1), charms(&[(&nft_app, Data::from(&content))]))], vec![]);
+        assert_eq!(
+            validate(&nft_app, &burn, &Data::empty(), &Data::empty()),
+            Err(ContractError::NftStillRequired)
+        );
+    }
+}
+
+/// Destination scripts of the test allowlist.
+const ALLOWLIST: [&[u8]; 3] = [b"alice", b"bob", b"carol"];
+
+/// Builds the leaves of the test allowlist.
+fn allowlist_leaves() -> Vec<B32> {
+    ALLOWLIST.iter().map(|dest| leaf_hash(dest)).collect()
+}
+
+/// Builds the proofs that each output in `outputs` pays the allowlist entry at its index.
+fn allowlist_proofs(outputs: &[(usize, usize)]) -> BTreeMap<usize, Vec<B32>> {
+    outputs
+        .iter()
+        .map(|&(output, entry)| (output, merkle_proof(&allowlist_leaves(), entry).unwrap()))
+        .collect()
+}
+
+/// Builds an NFT restricting its tokens to the test allowlist.
+fn allowlisted_nft(max_supply: u64, minted: u64) -> NftContent {
+    NftContent {
+        allowlist_root: merkle_root(&allowlist_leaves()),
+        ..tracked_nft(max_supply, minted)
+    }
+}
+
+/// Wraps an amount in the allowlisted token form.
+fn allowlisted(amount: u64) -> Data {
+    Data::from(&TokenAmount::Allowlisted {
+        allowlisted: amount,
+    })
+}
+
+/// Builds the native outputs paying each destination script in `dests`.
+fn coin_outs(dests: &[&[u8]]) -> Vec<NativeOutput> {
+    dests
+        .iter()
+        .map(|dest| NativeOutput {
+            amount: 1000,
+            dest: dest.to_vec(),
+        })
+        .collect()
+}
+
+/// Tests that Merkle proofs verify exactly the leaves they were built for.
+#[test]
+fn test_merkle_proof_round_trip() {
+    let leaves = allowlist_leaves();
+    let root = merkle_root(&leaves).unwrap();
+
+    for (index, leaf) in leaves.iter().enumerate() {
+        let proof = merkle_proof(&leaves, index).unwrap();
+        assert!(verify_proof(&root, leaf, &proof));
+        assert!(!verify_proof(&root, &leaf_hash(b"mallory"), &proof));
+    }
+    assert_eq!(merkle_root(&leaves[..1]), Some(leaves[0].clone()));
+    assert_eq!(merkle_root(&[]), None);
+    assert_eq!(merkle_proof(&leaves, 3), None);
+}
+
+/// Tests that an NFT with an allowlist only mints allowlisted tokens to allowlisted
+/// destinations, like `spells/mint-allowlisted.yaml`.
+#[test]
+fn test_validate_mint_allowlisted() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let mint = |amount: Data, dests: Option<Vec<NativeOutput>>| Transaction {
+        coin_outs: dests,
+        ..tx(
+            vec![(utxo(1), charms(&[(&nft_app, Data::from(&allowlisted_nft(100, 0)))]))],
+            vec![
+                charms(&[(&token_app, amount)]),
+                charms(&[(&nft_app, Data::from(&allowlisted_nft(100, 10)))]),
+            ],
+        )
+    };
+    let proofs = |outputs: &[(usize, usize)]| {
+        Data::from(&MintWitness {
+            allowlist_proofs: allowlist_proofs(outputs),
+            ..Default::default()
+        })
+    };
+    let x = Data::empty();
+    let to_bob = Some(coin_outs(&[b"bob", b"issuer"]));
+
+    assert_eq!(
+        validate(&token_app, &mint(allowlisted(10), to_bob.clone()), &x, &proofs(&[(0, 1)])),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&nft_app, &mint(allowlisted(10), to_bob.clone()), &x, &proofs(&[(0, 1)])),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &mint(tokens(10), to_bob.clone()), &x, &proofs(&[(0, 1)])),
+        Err(ContractError::TokenFormMismatch)
+    );
+    assert_eq!(
+        validate(&token_app, &mint(allowlisted(10), to_bob.clone()), &x, &Data::empty()),
+        Err(ContractError::MissingAllowlistProof(0))
+    );
+    assert_eq!(
+        validate(&token_app, &mint(allowlisted(10), to_bob), &x, &proofs(&[(0, 2)])),
+        Err(ContractError::NotAllowlisted(0))
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &mint(allowlisted(10), Some(coin_outs(&[b"mallory", b"issuer"]))),
+            &x,
+            &proofs(&[(0, 1)])
+        ),
+        Err(ContractError::NotAllowlisted(0))
+    );
+    assert_eq!(
+        validate(&token_app, &mint(allowlisted(10), None), &x, &proofs(&[(0, 1)])),
+        Err(ContractError::MissingDestination(0))
+    );
+}
+
+/// Tests that allowlisted tokens only move between allowlisted destinations, like
+/// `spells/transfer-allowlisted.yaml`.
+#[test]
+fn test_validate_transfer_allowlisted() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let send = |refs: Vec<(UtxoId, Charms)>, outs: Vec<Data>, dests: &[&[u8]]| Transaction {
+        refs,
+        coin_outs: Some(coin_outs(dests)),
+        ..tx(
+            vec![(utxo(2), charms(&[(&token_app, allowlisted(420))]))],
+            outs.into_iter()
+                .map(|amount| charms(&[(&token_app, amount)]))
+                .collect(),
+        )
+    };
+    let nft_ref =
+        || vec![(utxo(1), charms(&[(&nft_app, Data::from(&allowlisted_nft(1000, 420)))]))];
+    let proofs = |outputs: &[(usize, usize)]| {
+        Data::from(&TransferWitness {
+            allowlist_proofs: allowlist_proofs(outputs),
+        })
+    };
+    let x = Data::empty();
+
+    let split = send(nft_ref(), vec![allowlisted(400), allowlisted(20)], &[b"carol", b"alice"]);
+    assert_eq!(validate(&token_app, &split, &x, &proofs(&[(0, 2), (1, 0)])), Ok(()));
+    assert_eq!(
+        validate(
+            &token_app,
+            &split,
+            &declare(ContractInput::Transfer),
+            &proofs(&[(0, 2), (1, 0)])
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &split, &x, &proofs(&[(0, 2)])),
+        Err(ContractError::MissingAllowlistProof(1))
+    );
+
+    let unlisted = send(nft_ref(), vec![allowlisted(420)], &[b"mallory"]);
+    assert_eq!(
+        validate(&token_app, &unlisted, &x, &proofs(&[(0, 0)])),
+        Err(ContractError::NotAllowlisted(0))
+    );
+
+    let laundered = send(nft_ref(), vec![tokens(420)], &[b"mallory"]);
+    assert_eq!(
+        validate(&token_app, &laundered, &x, &Data::empty()),
+        Err(ContractError::TokenFormMismatch)
+    );
+
+    let hidden = send(Vec::new(), vec![allowlisted(420)], &[b"alice"]);
+    assert_eq!(
+        validate(&token_app, &hidden, &x, &proofs(&[(0, 0)])),
+        Err(ContractError::AllowlistUnavailable)
+    );
+
+    assert_eq!(
+        validate(&token_app, &send(Vec::new(), vec![tokens(420)], &[b"alice"]), &x, &x),
+        Err(ContractError::TokenFormMismatch)
+    );
+}
+
+/// Allocations of the test airdrop, by index.
+const ALLOCATIONS: [(&[u8], u64); 3] = [(b"alice", 100), (b"bob", 250), (b"carol", 50)];
+
+/// Builds the leaves of the test airdrop.
+fn allocation_leaves() -> Vec<B32> {
+    (0u32..)
+        .zip(ALLOCATIONS)
+        .map(|(index, (dest, amount))| airdrop_leaf(index, dest, amount))
+        .collect()
+}
+
+/// Builds a reserve NFT with the test airdrop, `claimed` allocations already claimed.
+fn airdrop_nft(claimed: &[u32]) -> NftContent {
+    let mut airdrop = Airdrop {
+        root: merkle_root(&allocation_leaves()).unwrap(),
+        claimed: Vec::new(),
+    };
+    let mut content = tracked_nft(1000, 0);
+    for &index in claimed {
+        airdrop.set_claimed(index);
+        content.remaining -= ALLOCATIONS[index as usize].1;
+        content.minted += ALLOCATIONS[index as usize].1;
+    }
+    NftContent {
+        airdrop: Some(airdrop),
+        ..content
+    }
+}
+
+/// Builds the witness claiming allocation `index` into output `output`.
+fn claim(index: u32, output: usize) -> AirdropClaim {
+    AirdropClaim {
+        index,
+        output,
+        amount: ALLOCATIONS[index as usize].1,
+        proof: merkle_proof(&allocation_leaves(), index as usize).unwrap(),
+    }
+}
+
+/// Wraps claims into a [`ClaimWitness`].
+fn claims(claims: Vec<AirdropClaim>) -> Data {
+    Data::from(&ClaimWitness {
+        claims,
+        ..Default::default()
+    })
+}
+
+/// Builds a claim transaction paying each of `payouts` to its destination, followed by the NFT.
+fn claim_tx(nft_in: &NftContent, nft_out: &NftContent, payouts: &[(&[u8], u64)]) -> Transaction {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let mut outs: Vec<Charms> = payouts
+        .iter()
+        .map(|&(_, amount)| charms(&[(&token_app, tokens(amount))]))
+        .collect();
+    outs.push(charms(&[(&nft_app, Data::from(nft_out))]));
+    let mut dests: Vec<&[u8]> = payouts.iter().map(|&(dest, _)| dest).collect();
+    dests.push(b"reserve");
+    Transaction {
+        coin_ins: Some(coin_outs(&[b"reserve"])),
+        coin_outs: Some(coin_outs(&dests)),
+        ..tx(vec![(utxo(1), charms(&[(&nft_app, Data::from(nft_in))]))], outs)
+    }
+}
+
+/// Tests that allocations are claimed exactly once, like `spells/claim-airdrop.yaml`.
+#[test]
+fn test_validate_claim_airdrop() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let x = Data::empty();
+
+    let bob = claim_tx(&airdrop_nft(&[0]), &airdrop_nft(&[0, 1]), &[(b"bob", 250)]);
+    let w = claims(vec![claim(1, 0)]);
+    assert_eq!(validate(&token_app, &bob, &x, &w), Ok(()));
+    assert_eq!(validate(&nft_app, &bob, &x, &w), Ok(()));
+    assert_eq!(validate(&token_app, &bob, &declare(ContractInput::Claim), &w), Ok(()));
+    assert_eq!(validate(&nft_app, &bob, &declare(ContractInput::Claim), &w), Ok(()));
+
+    let both = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[1, 2]), &[(b"carol", 50), (b"bob", 250)]);
+    assert_eq!(validate(&token_app, &both, &x, &claims(vec![claim(2, 0), claim(1, 1)])), Ok(()));
+
+    let again = claim_tx(&airdrop_nft(&[1]), &airdrop_nft(&[1]), &[(b"bob", 250)]);
+    let mut twice = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[1]), &[(b"bob", 250)]);
+    twice.outs.insert(0, charms(&[(&token_app, tokens(250))]));
+    for (tx, w) in [
+        (&again, claims(vec![claim(1, 0)])),
+        (&twice, claims(vec![claim(1, 0), claim(1, 1)])),
+    ] {
+        assert_eq!(validate(&token_app, tx, &x, &w), Err(ContractError::AlreadyClaimed(1)));
+    }
+}
+
+/// Tests that claims must pay the committed amount to the committed destination.
+#[test]
+fn test_validate_claim_airdrop_rejected() {
+    let token_app = app(TOKEN);
+    let x = Data::empty();
+    let w = claims(vec![claim(1, 0)]);
+    let claim_bob = |payout: (&[u8], u64), nft_out: &NftContent| {
+        validate(&token_app, &claim_tx(&airdrop_nft(&[]), nft_out, &[payout]), &x, &w)
+    };
+
+    assert_eq!(
+        claim_bob((b"mallory", 250), &airdrop_nft(&[1])),
+        Err(ContractError::InvalidClaim(1))
+    );
+    assert_eq!(
+        claim_bob((b"bob", 300), &airdrop_nft(&[1])),
+        Err(ContractError::InvalidClaim(1))
+    );
+    assert_eq!(
+        claim_bob(
+            (b"bob", 250),
+            &NftContent {
+                airdrop: airdrop_nft(&[]).airdrop,
+                ..airdrop_nft(&[1])
+            }
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+
+    let mut extra = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[1]), &[(b"bob", 250)]);
+    extra.outs.push(charms(&[(&token_app, tokens(10))]));
+    assert_eq!(
+        validate(&token_app, &extra, &x, &w),
+        Err(ContractError::ClaimAmountMismatch {
+            minted: 260,
+            claimed: 250
+        })
+    );
+
+    let bob = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[1]), &[(b"bob", 250)]);
+    assert_eq!(
+        validate(&token_app, &bob, &x, &Data::empty()),
+        Err(ContractError::MissingClaimWitness)
+    );
+    let reserve = claim_tx(&tracked_nft(1000, 0), &tracked_nft(1000, 250), &[(b"bob", 250)]);
+    assert_eq!(
+        validate(&token_app, &reserve, &declare(ContractInput::Claim), &w),
+        Err(ContractError::MissingAirdrop)
+    );
+}
+
+/// Tests that the supply of an NFT with an airdrop is only minted by claims.
+#[test]
+fn test_validate_mint_airdrop_reserve() {
+    let reserved = NftContent {
+        remaining: 600,
+        minted: 400,
+        ..airdrop_nft(&[])
+    };
+    let mint = supply_tx(&airdrop_nft(&[]), 0, &reserved, 400);
+
+    assert_eq!(
+        validate(&app(TOKEN), &timelocked(mint.clone(), 1), &mint_at(1), &Data::empty()),
+        Err(ContractError::AirdropOnly)
+    );
+    assert_eq!(
+        validate(&app(TOKEN), &mint, &Data::empty(), &Data::empty()),
+        Err(ContractError::MissingClaimWitness)
+    );
+}
+
+/// Tests that claims count against the per-transaction cap like any other mint.
+#[test]
+fn test_validate_claim_airdrop_capped() {
+    let capped = |content: NftContent| NftContent {
+        max_mint_per_tx: Some(200),
+        ..content
+    };
+    let w = claims(vec![claim(1, 0)]);
+
+    let bob = claim_tx(&capped(airdrop_nft(&[])), &capped(airdrop_nft(&[1])), &[(b"bob", 250)]);
+    assert_eq!(
+        validate(&app(TOKEN), &bob, &declare(ContractInput::Claim), &w),
+        Err(ContractError::MintCapExceeded {
+            minted: 250,
+            cap: 200
+        })
+    );
+    let carol = claim_tx(&capped(airdrop_nft(&[])), &capped(airdrop_nft(&[2])), &[(b"carol", 50)]);
+    assert_eq!(
+        validate(&app(TOKEN), &carol, &declare(ContractInput::Claim), &claims(vec![claim(2, 0)])),
+        Ok(())
+    );
+}
+
+/// Tests that an NFT with an airdrop, which anyone can spend, stays at its
+/// script and cannot be finalized, paused, updated or burned.
+#[test]
+fn test_validate_airdrop_nft_locked() {
+    let nft_app = app(NFT);
+    let x = Data::empty();
+    let w = claims(vec![claim(1, 0)]);
+
+    let mut moved = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[1]), &[(b"bob", 250)]);
+    moved.coin_outs = Some(coin_outs(&[b"bob", b"mallory"]));
+    assert_eq!(validate(&nft_app, &moved, &x, &w), Err(ContractError::AirdropNftMoved));
+    let mut kept = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[]), &[]);
+    assert_eq!(validate(&nft_app, &kept, &declare(ContractInput::Transfer), &x), Ok(()));
+    kept.coin_outs = Some(coin_outs(&[b"mallory"]));
+    assert_eq!(
+        validate(&nft_app, &kept, &declare(ContractInput::Transfer), &x),
+        Err(ContractError::AirdropNftMoved)
+    );
+
+    // a copy of the NFT would let every allocation be claimed again.
+    let mut forked = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[]), &[]);
+    forked
+        .outs
+        .push(charms(&[(&nft_app, Data::from(&airdrop_nft(&[])))]));
+    forked.coin_outs = Some(coin_outs(&[b"reserve", b"mallory"]));
+    for x in [Data::empty(), declare(ContractInput::Transfer)] {
+        assert_eq!(
+            validate(&nft_app, &forked, &x, &Data::empty()),
+            Err(ContractError::DuplicateOutputNft(2))
+        );
+    }
+    let mut double = claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[1]), &[(b"bob", 250)]);
+    double
+        .outs
+        .push(charms(&[(&nft_app, Data::from(&airdrop_nft(&[])))]));
+    double.coin_outs = Some(coin_outs(&[b"bob", b"reserve", b"mallory"]));
+    for app in [&nft_app, &app(TOKEN)] {
+        assert_eq!(
+            validate(app, &double, &declare(ContractInput::Claim), &w),
+            Err(ContractError::DuplicateOutputNft(2))
+        );
+    }
+
+    let pausable = NftContent {
+        pausable: true,
+        ..airdrop_nft(&[])
+    };
+    let changes = [
+        (
+            airdrop_nft(&[]),
+            NftContent {
+                finalized: true,
+                ..airdrop_nft(&[])
+            },
+        ),
+        (
+            pausable.clone(),
+            NftContent {
+                paused: true,
+                ..pausable
+            },
+        ),
+        (
+            airdrop_nft(&[]),
+            NftContent {
+                uri: Some("https://mallory.example".to_string()),
+                ..airdrop_nft(&[])
+            },
+        ),
+    ];
+    for (nft_in, nft_out) in changes {
+        assert_eq!(
+            validate(&nft_app, &claim_tx(&nft_in, &nft_out, &[]), &x, &x),
+            Err(ContractError::AirdropNftLocked)
+        );
+    }
+    assert_eq!(
+        validate(
+            &nft_app,
+            &claim_tx(&airdrop_nft(&[]), &airdrop_nft(&[]), &[]),
+            &declare(ContractInput::UpdateMetadata),
+            &x
+        ),
+        Err(ContractError::AirdropNftLocked)
+    );
+
+    let burnable = NftContent {
+        burnable: true,
+        ..airdrop_nft(&[])
+    };
+    let burn = tx(vec![(utxo(1), charms(&[(&nft_app, Data::from(&burnable))]))], vec![]);
+    assert_eq!(
+        validate(&nft_app, &burn, &x, &x),
+        Err(ContractError::NftNotBurnable { remaining: 1000 })
+    );
+}
+
+/// Builds a transaction spending `ins` and paying each of `outs` to its destination.
+fn swap_tx(ins: Vec<(UtxoId, Charms)>, outs: Vec<(&[u8], u64, Charms)>) -> Transaction {
+    let coin_outs = outs
+        .iter()
+        .map(|(dest, amount, _)| NativeOutput {
+            amount: *amount,
+            dest: dest.to_vec(),
+        })
+        .collect();
+    Transaction {
+        coin_outs: Some(coin_outs),
+        ..tx(ins, outs.into_iter().map(|(_, _, charms)| charms).collect())
+    }
+}
+
+/// Builds the order of alice giving `give` tokens for `want` of `want_token`.
+fn swap_order(give: u64, want_token: Option<B32>, want: u64) -> Data {
+    Data::from(&SwapOrder {
+        give,
+        want_token,
+        want,
+        maker_dest: b"alice".to_vec(),
+    })
+}
+
+/// Tests that a token-for-token swap settles both legs as ordered, like
+/// `spells/swap-tokens.yaml`.
+#[test]
+fn test_validate_swap_tokens() {
+    let token_app = app(TOKEN);
+    let other_app = App {
+        identity: hash("other-token"),
+        ..app(TOKEN)
+    };
+    let swap = |to_bob: u64, to_alice: u64| {
+        swap_tx(
+            vec![
+                (utxo(1), charms(&[(&token_app, tokens(500))])),
+                (utxo(2), charms(&[(&other_app, tokens(30))])),
+            ],
+            vec![
+                (b"bob", 1000, charms(&[(&token_app, tokens(to_bob))])),
+                (b"alice", 1000, charms(&[(&token_app, tokens(500 - to_bob))])),
+                (b"alice", 1000, charms(&[(&other_app, tokens(to_alice))])),
+                (b"bob", 1000, charms(&[(&other_app, tokens(30 - to_alice))])),
+            ],
+        )
+    };
+    let order = swap_order(400, Some(other_app.identity.clone()), 30);
+
+    assert_eq!(validate(&token_app, &swap(400, 30), &Data::empty(), &order), Ok(()));
+    assert_eq!(
+        validate(&token_app, &swap(400, 30), &declare(ContractInput::Swap), &order),
+        Ok(())
+    );
+    assert_eq!(validate(&other_app, &swap(400, 30), &Data::empty(), &Data::empty()), Ok(()));
+    assert_eq!(
+        validate(&token_app, &swap(400, 25), &Data::empty(), &order),
+        Err(ContractError::SwapUnderpaid {
+            wanted: 30,
+            paid: 25
+        })
+    );
+    assert_eq!(
+        validate(&token_app, &swap(450, 30), &Data::empty(), &order),
+        Err(ContractError::SwapGiveMismatch {
+            order: 400,
+            given: 450
+        })
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &swap(400, 30),
+            &Data::empty(),
+            &swap_order(400, Some(token_app.identity.clone()), 30)
+        ),
+        Err(ContractError::InvalidSwapOrder)
+    );
+    assert_eq!(
+        validate(&token_app, &swap(400, 30), &declare(ContractInput::Swap), &Data::empty()),
+        Err(ContractError::InvalidSwapOrder)
+    );
+    assert_eq!(
+        validate(&app(NFT), &swap(400, 30), &declare(ContractInput::Swap), &order),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Tests that a token-for-bitcoin swap pays the maker the ordered sats, like
+/// `spells/swap-btc.yaml`.
+#[test]
+fn test_validate_swap_btc() {
+    let token_app = app(TOKEN);
+    let swap = |to_bob: u64, sats: u64| {
+        swap_tx(
+            vec![(utxo(1), charms(&[(&token_app, tokens(500))]))],
+            vec![
+                (b"bob", 1000, charms(&[(&token_app, tokens(to_bob))])),
+                (b"alice", sats, Charms::new()),
+                (b"alice", 1000, charms(&[(&token_app, tokens(500 - to_bob))])),
+            ],
+        )
+    };
+    let order = swap_order(400, None, 50_000);
+
+    assert_eq!(validate(&token_app, &swap(400, 49_000), &Data::empty(), &order), Ok(()));
+    assert_eq!(
+        validate(&token_app, &swap(400, 40_000), &Data::empty(), &order),
+        Err(ContractError::SwapUnderpaid {
+            wanted: 50_000,
+            paid: 41_000
+        })
+    );
+    assert_eq!(
+        validate(&token_app, &swap(500, 60_000), &Data::empty(), &order),
+        Err(ContractError::SwapGiveMismatch {
+            order: 400,
+            given: 500
+        })
+    );
+
+    let unpriced = Transaction {
+        coin_outs: None,
+        ..swap(400, 49_000)
+    };
+    assert_eq!(
+        validate(&token_app, &unpriced, &Data::empty(), &order),
+        Err(ContractError::MissingDestination(0))
+    );
+    let minting = Transaction {
+        ins: Vec::new(),
+        ..swap(400, 49_000)
+    };
+    assert_eq!(
+        validate(&token_app, &minting, &Data::empty(), &order),
+        Err(ContractError::UnbalancedTransfer {
+            input: 0,
+            output: 500
+        })
+    );
+}
+
+/// Builds alice's order selling tokens at `price` sats each, signed by the key
+/// with seed `signer`.
+fn limit_order(price: u64, signer: u8) -> LimitOrder {
+    let mut order = LimitOrder {
+        maker: hex::encode(authority(&authority_key(1)).0),
+        maker_dest: b"alice".to_vec(),
+        price,
+        signature: String::new(),
+    };
+    let message = order_message(&app(TOKEN), &order);
+    order.signature = hex::encode(
+        authority_key(signer)
+            .sign_raw(&message.0, &[0; 32])
+            .unwrap()
+            .to_bytes(),
+    );
+    order
+}
+
+/// Wraps an amount offered under `order` in the offered token form.
+fn offered(amount: u64, order: &LimitOrder) -> Data {
+    Data::from(&TokenAmount::Offered {
+        offered: amount,
+        order: Box::new(order.clone()),
+    })
+}
+
+/// Declares a fill.
+fn fill() -> Data {
+    declare(ContractInput::Fill)
+}
+
+/// Builds the fill of 500 offered tokens, paying `sats` to alice and carrying
+/// `remainder` under `carried` to a new order output.
+fn fill_tx(order: &LimitOrder, sats: u64, remainder: u64, carried: &LimitOrder) -> Transaction {
+    let token_app = app(TOKEN);
+    let mut outs: Vec<(&[u8], u64, Charms)> = vec![
+        (b"bob", 1000, charms(&[(&token_app, tokens(500 - remainder))])),
+        (b"alice", sats, Charms::new()),
+    ];
+    if remainder > 0 {
+        outs.push((b"order", 1000, charms(&[(&token_app, offered(remainder, carried))])));
+    }
+    swap_tx(vec![(utxo(1), charms(&[(&token_app, offered(500, order))]))], outs)
+}
+
+/// Tests that a taker fills part of a limit order, like `spells/fill-order.yaml`.
+#[test]
+fn test_validate_fill_order() {
+    let token_app = app(TOKEN);
+    let order = limit_order(100, 1);
+    let w = Data::empty();
+
+    let partial = fill_tx(&order, 20_000, 300, &order);
+    assert_eq!(validate(&token_app, &partial, &fill(), &w), Ok(()));
+    assert_eq!(validate(&token_app, &fill_tx(&order, 50_000, 0, &order), &fill(), &w), Ok(()));
+    assert_eq!(
+        validate(&token_app, &fill_tx(&order, 19_999, 300, &order), &fill(), &w),
+        Err(ContractError::OrderUnderpaid {
+            price: 20_000,
+            paid: 19_999
+        })
+    );
+    assert_eq!(
+        validate(&token_app, &fill_tx(&order, 20_000, 300, &limit_order(1, 1)), &fill(), &w),
+        Err(ContractError::OrderUnderpaid {
+            price: 50_000,
+            paid: 20_000
+        })
+    );
+    assert_eq!(validate(&token_app, &partial, &Data::empty(), &w), Ok(()));
+    assert_eq!(
+        validate(&token_app, &partial, &declare(ContractInput::Transfer), &w),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Tests that limit orders are only filled as signed.
+#[test]
+fn test_validate_fill_order_rejected() {
+    let token_app = app(TOKEN);
+    let order = limit_order(100, 1);
+    let w = Data::empty();
+
+    let forged = limit_order(100, 2);
+    assert_eq!(
+        validate(&token_app, &fill_tx(&forged, 20_000, 300, &forged), &fill(), &w),
+        Err(ContractError::InvalidOrderSignature)
+    );
+    let overfilled = Transaction {
+        ins: vec![(utxo(1), charms(&[(&token_app, offered(200, &order))]))],
+        ..fill_tx(&order, 20_000, 300, &order)
+    };
+    assert_eq!(
+        validate(&token_app, &overfilled, &fill(), &w),
+        Err(ContractError::InvalidOrderRemainder {
+            offered: 200,
+            remainder: 300
+        })
+    );
+    let two_orders = Transaction {
+        ins: vec![
+            (utxo(1), charms(&[(&token_app, offered(250, &order))])),
+            (utxo(2), charms(&[(&token_app, offered(250, &order))])),
+        ],
+        ..fill_tx(&order, 20_000, 300, &order)
+    };
+    assert_eq!(
+        validate(&token_app, &two_orders, &fill(), &w),
+        Err(ContractError::OrderCountMismatch(2))
+    );
+}
+
+/// Tests that a maker places an order by transfer and gets it back by cancelling it.
+#[test]
+fn test_validate_place_and_return_order() {
+    let token_app = app(TOKEN);
+    let order = limit_order(100, 1);
+    let w = Data::empty();
+
+    let place = swap_tx(
+        vec![(utxo(1), charms(&[(&token_app, tokens(500))]))],
+        vec![(b"order", 1000, charms(&[(&token_app, offered(500, &order))]))],
+    );
+    assert_eq!(validate(&token_app, &place, &Data::empty(), &w), Ok(()));
+
+    let give_back = |dest: &'static [u8]| {
+        swap_tx(
+            vec![(utxo(1), charms(&[(&token_app, offered(500, &order))]))],
+            vec![(dest, 1000, charms(&[(&token_app, tokens(500))]))],
+        )
+    };
+    assert_eq!(validate(&token_app, &give_back(b"alice"), &fill(), &w), Ok(()));
+    assert_eq!(
+        validate(&token_app, &give_back(b"bob"), &fill(), &w),
+        Err(ContractError::OrderUnderpaid {
+            price: 50_000,
+            paid: 0
+        })
+    );
+
+    // part of the order can be cancelled while the rest stays on offer.
+    let shrink = swap_tx(
+        vec![(utxo(1), charms(&[(&token_app, offered(500, &order))]))],
+        vec![
+            (b"alice", 1000, charms(&[(&token_app, tokens(200))])),
+            (b"order", 1000, charms(&[(&token_app, offered(300, &order))])),
+        ],
+    );
+    assert_eq!(validate(&token_app, &shrink, &fill(), &w), Ok(()));
+
+    // tokens only count as returned when the maker can spend them freely.
+    let bound = swap_tx(
+        vec![(utxo(1), charms(&[(&token_app, offered(500, &order))]))],
+        vec![(
+            b"alice",
+            1000,
+            charms(&[(
+                &token_app,
+                vesting(VestingSchedule {
+                    beneficiary: b"bob".to_vec(),
+                    total: 500,
+                    ..schedule(0)
+                }),
+            )]),
+        )],
+    );
+    assert_eq!(
+        validate(&token_app, &bound, &fill(), &w),
+        Err(ContractError::OrderUnderpaid {
+            price: 50_000,
+            paid: 1000
+        })
+    );
+}
+
+/// Preimage of the test HTLC payment hash.
+const PREIMAGE: [u8; 32] = [42; 32];
+
+/// Builds the HTLC paying bob for the preimage, refundable to alice after height 900,000.
+fn htlc() -> Htlc {
+    Htlc {
+        payment_hash: hex::encode(Sha256::digest(PREIMAGE)),
+        recipient_dest: b"bob".to_vec(),
+        refund_dest: b"alice".to_vec(),
+        timeout_height: 900_000,
+    }
+}
+
+/// Wraps an amount locked in the test HTLC in the locked token form.
+fn locked(amount: u64) -> Data {
+    Data::from(&TokenAmount::Locked {
+        locked: amount,
+        htlc: Box::new(htlc()),
+    })
+}
+
+/// Builds the witness revealing `preimage`.
+fn reveal(preimage: &[u8]) -> Data {
+    Data::from(&HtlcWitness {
+        preimage: hex::encode(preimage),
+    })
+}
+
+/// Builds a transaction unlocking 500 tokens from the test HTLC to `dest`.
+fn unlock_tx(dest: &'static [u8]) -> Transaction {
+    let token_app = app(TOKEN);
+    swap_tx(
+        vec![(utxo(1), charms(&[(&token_app, locked(500))]))],
+        vec![(dest, 1000, charms(&[(&token_app, tokens(500))]))],
+    )
+}
+
+/// Tests that the recipient claims an HTLC with the preimage, like
+/// `spells/claim-htlc.yaml`.
+#[test]
+fn test_validate_claim_htlc() {
+    let token_app = app(TOKEN);
+    let x = Data::empty();
+
+    let lock = swap_tx(
+        vec![(utxo(1), charms(&[(&token_app, tokens(500))]))],
+        vec![(b"htlc", 1000, charms(&[(&token_app, locked(500))]))],
+    );
+    assert_eq!(validate(&token_app, &lock, &x, &Data::empty()), Ok(()));
+
+    assert_eq!(validate(&token_app, &unlock_tx(b"bob"), &x, &reveal(&PREIMAGE)), Ok(()));
+    assert_eq!(
+        validate(
+            &token_app,
+            &unlock_tx(b"bob"),
+            &declare(ContractInput::Claim),
+            &reveal(&PREIMAGE)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &unlock_tx(b"bob"), &x, &reveal(&[7; 32])),
+        Err(ContractError::InvalidPreimage)
+    );
+    assert_eq!(
+        validate(&token_app, &unlock_tx(b"bob"), &x, &Data::empty()),
+        Err(ContractError::InvalidPreimage)
+    );
+    assert_eq!(
+        validate(&token_app, &unlock_tx(b"mallory"), &x, &reveal(&PREIMAGE)),
+        Err(ContractError::HtlcPayoutMismatch {
+            locked: 500,
+            paid: 0
+        })
+    );
+    // relocking the tokens to bob under terms only the sender meets pays nothing.
+    let relocked = swap_tx(
+        vec![(utxo(1), charms(&[(&token_app, locked(500))]))],
+        vec![(b"bob", 1000, charms(&[(&token_app, locked(500))]))],
+    );
+    assert_eq!(
+        validate(&token_app, &relocked, &x, &reveal(&PREIMAGE)),
+        Err(ContractError::HtlcPayoutMismatch {
+            locked: 500,
+            paid: 0
+        })
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &unlock_tx(b"bob"),
+            &declare(ContractInput::Transfer),
+            &reveal(&PREIMAGE)
+        ),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Tests that the sender refunds an HTLC only after its timeout, like
+/// `spells/refund-htlc.yaml`.
+#[test]
+fn test_validate_refund_htlc() {
+    let token_app = app(TOKEN);
+    let refund_at = |height| declare_proven(ContractInput::Refund { height }, height);
+    let refund = |dest, height| timelocked(unlock_tx(dest), height);
+    let w = Data::empty();
+
+    assert_eq!(
+        validate(&token_app, &refund(b"alice", 900_001), &refund_at(900_001), &w),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &refund(b"alice", 900_000), &refund_at(900_000), &w),
+        Err(ContractError::HtlcNotExpired {
+            timeout_height: 900_000
+        })
+    );
+    assert_eq!(
+        validate(&token_app, &refund(b"bob", 900_001), &refund_at(900_001), &w),
+        Err(ContractError::HtlcPayoutMismatch {
+            locked: 500,
+            paid: 0
+        })
+    );
+
+    let plain = timelocked(
+        swap_tx(
+            vec![(utxo(1), charms(&[(&token_app, tokens(500))]))],
+            vec![(b"alice", 1000, charms(&[(&token_app, tokens(500))]))],
+        ),
+        900_001,
+    );
+    assert_eq!(
+        validate(&token_app, &plain, &refund_at(900_001), &w),
+        Err(ContractError::HtlcCountMismatch(0))
+    );
+    assert_eq!(
+        validate(&app(NFT), &plain, &refund_at(900_001), &w),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Tests that a refund cannot claim a height its timelocked input does not prove.
+#[test]
+fn test_validate_refund_htlc_unproven_height() {
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+
+    let early = timelocked(unlock_tx(b"alice"), 800_000);
+    let claimed = Data::from(&PublicInput {
+        version: 1,
+        op: ContractInput::Refund { height: u64::MAX },
+        height_proof: None,
+    });
+    assert_eq!(
+        validate(&token_app, &early, &claimed, &w),
+        Err(ContractError::MissingHeightProof)
+    );
+    let overclaimed = declare_proven(ContractInput::Refund { height: 900_001 }, 800_000);
+    assert_eq!(
+        validate(&token_app, &early, &overclaimed, &w),
+        Err(ContractError::HeightNotProven {
+            height: 900_001,
+            proven: 800_000
+        })
+    );
+}
+
+/// Builds bob's schedule vesting 1000 tokens over blocks 100 to 300, with a cliff at 150.
+fn schedule(released: u64) -> VestingSchedule {
+    VestingSchedule {
+        beneficiary: b"bob".to_vec(),
+        total: 1000,
+        start: 100,
+        cliff: 50,
+        duration: 200,
+        released,
+    }
+}
+
+/// Wraps a schedule in the vesting token form.
+fn vesting(schedule: VestingSchedule) -> Data {
+    Data::from(&TokenAmount::Vesting {
+        vesting: Box::new(schedule),
+    })
+}
+
+/// Declares a release at `height`, proven by the input [`timelocked`] adds.
+fn release_at(height: u64) -> Data {
+    declare_proven(ContractInput::Release { height }, height)
+}
+
+/// Builds a release from `released_in` to `released_out` paying `paid` tokens to
+/// `dest`; the schedule is not re-emitted once fully released.
+fn release_tx(released_in: u64, released_out: u64, dest: &'static [u8], paid: u64) -> Transaction {
+    let token_app = app(TOKEN);
+    let mut outs: Vec<(&[u8], u64, Charms)> =
+        vec![(dest, 1000, charms(&[(&token_app, tokens(paid))]))];
+    if released_out < 1000 {
+        outs.push((b"vault", 1000, charms(&[(&token_app, vesting(schedule(released_out)))])));
+    }
+    swap_tx(vec![(utxo(1), charms(&[(&token_app, vesting(schedule(released_in)))]))], outs)
+}
+
+/// Tests that nothing vests before the cliff and everything at the end of the schedule.
+#[test]
+fn test_vested_at_edges() {
+    let schedule = schedule(0);
+
+    assert_eq!(schedule.vested_at(0), 0);
+    assert_eq!(schedule.vested_at(100), 0);
+    assert_eq!(schedule.vested_at(149), 0);
+    assert_eq!(schedule.vested_at(150), 250);
+    assert_eq!(schedule.vested_at(200), 500);
+    assert_eq!(schedule.vested_at(299), 995);
+    assert_eq!(schedule.vested_at(300), 1000);
+    assert_eq!(schedule.vested_at(u64::MAX), 1000);
+}
+
+/// Tests schedules without a duration, with a cliff past the duration, and at the
+/// largest amounts.
+#[test]
+fn test_vested_at_degenerate_schedules() {
+    let immediate = VestingSchedule {
+        cliff: 0,
+        duration: 0,
+        ..schedule(0)
+    };
+    assert_eq!(immediate.vested_at(99), 0);
+    assert_eq!(immediate.vested_at(100), 1000);
+
+    let late_cliff = VestingSchedule {
+        cliff: 300,
+        ..schedule(0)
+    };
+    assert_eq!(late_cliff.vested_at(399), 0);
+    assert_eq!(late_cliff.vested_at(400), 1000);
+
+    let huge = VestingSchedule {
+        total: u64::MAX,
+        start: u64::MAX - 200,
+        ..schedule(0)
+    };
+    assert_eq!(huge.vested_at(u64::MAX - 1), u64::MAX / 200 * 199 + 14);
+    assert_eq!(huge.vested_at(u64::MAX), u64::MAX);
+}
+
+/// Tests that vested tokens are released to the beneficiary, like
+/// `spells/release-vested.yaml`.
+#[test]
+fn test_validate_release_vested() {
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(0, 500, b"bob", 500), 200),
+            &release_at(200),
+            &w
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(500, 500, b"bob", 0), 200),
+            &release_at(200),
+            &w
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(200, 1000, b"bob", 800), 300),
+            &release_at(300),
+            &w
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(0, 501, b"bob", 501), 200),
+            &release_at(200),
+            &w
+        ),
+        Err(ContractError::VestingExceeded {
+            vested: 500,
+            released: 501
+        })
+    );
+    assert_eq!(
+        validate(&token_app, &timelocked(release_tx(0, 1, b"bob", 1), 149), &release_at(149), &w),
+        Err(ContractError::VestingExceeded {
+            vested: 0,
+            released: 1
+        })
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(200, 1000, b"bob", 800), 299),
+            &release_at(299),
+            &w
+        ),
+        Err(ContractError::VestingExceeded {
+            vested: 995,
+            released: 1000
+        })
+    );
+}
+
+/// Tests that a release keeps the schedule terms and pays the beneficiary.
+#[test]
+fn test_validate_release_vested_rejected() {
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(0, 500, b"mallory", 500), 200),
+            &release_at(200),
+            &w
+        ),
+        Err(ContractError::VestingPayoutMismatch {
+            released: 500,
+            paid: 0
+        })
+    );
+    let mut bound = release_tx(0, 500, b"bob", 500);
+    bound.outs[0] = charms(&[(&token_app, locked(500))]);
+    assert_eq!(
+        validate(&token_app, &timelocked(bound, 200), &release_at(200), &w),
+        Err(ContractError::VestingPayoutMismatch {
+            released: 500,
+            paid: 0
+        })
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(300, 200, b"bob", 0), 200),
+            &release_at(200),
+            &w
+        ),
+        Err(ContractError::VestingStateChanged)
+    );
+    let redirected = Transaction {
+        outs: vec![
+            charms(&[(&token_app, tokens(500))]),
+            charms(&[(
+                &token_app,
+                vesting(VestingSchedule {
+                    beneficiary: b"mallory".to_vec(),
+                    ..schedule(500)
+                }),
+            )]),
+        ],
+        ..release_tx(0, 500, b"bob", 500)
+    };
+    assert_eq!(
+        validate(&token_app, &timelocked(redirected, 200), &release_at(200), &w),
+        Err(ContractError::VestingStateChanged)
+    );
+    assert_eq!(
+        validate(&token_app, &release_tx(0, 500, b"bob", 500), &Data::empty(), &w),
+        Err(ContractError::MissingBlockHeight)
+    );
+    let unproven = Data::from(&PublicInput {
+        version: 1,
+        op: ContractInput::Release { height: u64::MAX },
+        height_proof: None,
+    });
+    assert_eq!(
+        validate(&token_app, &release_tx(0, 1000, b"bob", 1000), &unproven, &w),
+        Err(ContractError::MissingHeightProof)
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(release_tx(0, 1000, b"bob", 1000), 200),
+            &declare_proven(ContractInput::Release { height: 300 }, 200),
+            &w
+        ),
+        Err(ContractError::HeightNotProven {
+            height: 300,
+            proven: 200
+        })
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &release_tx(0, 500, b"bob", 500),
+            &declare(ContractInput::Transfer),
+            &w
+        ),
+        Err(ContractError::UnsupportedOperation)
+    );
+
+    let mut mixed = release_tx(0, 500, b"bob", 500);
+    mixed
+        .ins
+        .push((utxo(2), charms(&[(&token_app, locked(500))])));
+    mixed.outs.push(charms(&[(&token_app, tokens(500))]));
+    assert_eq!(
+        validate(&token_app, &timelocked(mixed, 200), &release_at(200), &w),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Builds a reserve NFT paying 1% of the stake per block, `minted` tokens minted so far.
+fn staking_nft(minted: u64) -> NftContent {
+    NftContent {
+        staking_rate: STAKING_RATE_SCALE / 100,
+        ..tracked_nft(1000, minted)
+    }
+}
+
+/// Wraps an amount in the staked token form.
+fn staked(amount: u64, since: u64) -> Data {
+    Data::from(&TokenAmount::Staked {
+        staked: amount,
+        since,
+    })
+}
+
+/// Declares an unstake at `height`, proven by the input [`timelocked`] adds.
+fn unstake_at(height: u64) -> Data {
+    declare_proven(ContractInput::Unstake { height }, height)
+}
+
+/// Builds an unstake of 100 tokens staked at height 500 that spends `nft_in`,
+/// recreates it as `nft_out` and pays out `paid` tokens.
+fn unstake_tx(nft_in: &NftContent, nft_out: &NftContent, paid: u64) -> Transaction {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    tx(
+        vec![
+            (utxo(1), charms(&[(&nft_app, Data::from(nft_in))])),
+            (utxo(2), charms(&[(&token_app, staked(100, 500))])),
+        ],
+        vec![
+            charms(&[(&token_app, tokens(paid))]),
+            charms(&[(&nft_app, Data::from(nft_out))]),
+        ],
+    )
+}
+
+/// Tests that tokens are staked at the declared height, like `spells/stake.yaml`.
+#[test]
+fn test_validate_stake() {
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+    let stake = tx(
+        vec![(utxo(1), charms(&[(&token_app, tokens(150))]))],
+        vec![
+            charms(&[(&token_app, staked(100, 500))]),
+            charms(&[(&token_app, tokens(50))]),
+        ],
+    );
+
+    assert_eq!(
+        validate(&token_app, &stake, &declare(ContractInput::Stake { height: 500 }), &w),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &stake, &declare(ContractInput::Stake { height: 510 }), &w),
+        Err(ContractError::InvalidStakeHeight {
+            height: 510,
+            since: 500
+        })
+    );
+    for x in [Data::empty(), declare(ContractInput::Transfer)] {
+        assert_eq!(validate(&token_app, &stake, &x, &w), Err(ContractError::UndeclaredStake));
+    }
+
+    let inflated = Transaction {
+        outs: vec![charms(&[(&token_app, staked(200, 500))])],
+        ..stake
+    };
+    assert_eq!(
+        validate(&token_app, &inflated, &declare(ContractInput::Stake { height: 500 }), &w),
+        Err(ContractError::UnbalancedTransfer {
+            input: 150,
+            output: 200
+        })
+    );
+}
+
+/// Tests that an unstake returns the principal with its reward minted from the
+/// reserve, like `spells/unstake.yaml`.
+#[test]
+fn test_validate_unstake() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+
+    let unstake = timelocked(unstake_tx(&staking_nft(0), &staking_nft(10), 110), 510);
+    assert_eq!(validate(&token_app, &unstake, &unstake_at(510), &w), Ok(()));
+    assert_eq!(validate(&nft_app, &unstake, &unstake_at(510), &w), Ok(()));
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&staking_nft(0), &staking_nft(0), 100), 500),
+            &unstake_at(500),
+            &w
+        ),
+        Ok(())
+    );
+
+    let finalized = NftContent {
+        finalized: true,
+        ..staking_nft(0)
+    };
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&finalized, &finalized, 110), 510),
+            &unstake_at(510),
+            &w
+        ),
+        Err(ContractError::SupplyFinalized)
+    );
+
+    let principal = tx(
+        vec![(utxo(2), charms(&[(&token_app, staked(100, 500))]))],
+        vec![charms(&[(&token_app, tokens(100))])],
+    );
+    assert_eq!(
+        validate(&token_app, &timelocked(principal.clone(), 510), &unstake_at(510), &w),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&token_app, &principal, &Data::empty(), &w),
+        Err(ContractError::MissingBlockHeight)
+    );
+}
+
+/// Tests that an unstake mints exactly the reward, and never more than the reserve.
+#[test]
+fn test_validate_unstake_rejected() {
+    let nft_app = app(NFT);
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+
+    let greedy = timelocked(unstake_tx(&staking_nft(0), &staking_nft(11), 111), 510);
+    for app in [&token_app, &nft_app] {
+        assert_eq!(
+            validate(app, &greedy, &unstake_at(510), &w),
+            Err(ContractError::StakeRewardMismatch {
+                reward: 10,
+                minted: 11
+            })
+        );
+    }
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&staking_nft(995), &staking_nft(1000), 110), 510),
+            &unstake_at(510),
+            &w
+        ),
+        Err(ContractError::RewardExceedsReserve {
+            reward: 10,
+            remaining: 5
+        })
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&staking_nft(0), &staking_nft(0), 110), 510),
+            &unstake_at(510),
+            &w
+        ),
+        Err(ContractError::MintAmountMismatch {
+            minted: 10,
+            supply_decrease: 0
+        })
+    );
+    let raised = NftContent {
+        staking_rate: STAKING_RATE_SCALE,
+        ..staking_nft(10)
+    };
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&staking_nft(0), &raised, 110), 510),
+            &unstake_at(510),
+            &w
+        ),
+        Err(ContractError::NftStateChanged)
+    );
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&staking_nft(0), &staking_nft(0), 100), 499),
+            &unstake_at(499),
+            &w
+        ),
+        Err(ContractError::InvalidStakeHeight {
+            height: 499,
+            since: 500
+        })
+    );
+
+    let unbounded = NftContent {
+        staking_rate: u64::MAX,
+        ..staking_nft(0)
+    };
+    assert_eq!(
+        validate(
+            &token_app,
+            &timelocked(unstake_tx(&unbounded, &unbounded, 100), LOCKTIME_THRESHOLD - 1),
+            &unstake_at(LOCKTIME_THRESHOLD - 1),
+            &w
+        ),
+        Err(ContractError::RewardExceedsReserve {
+            reward: u64::MAX,
+            remaining: 1000
+        })
+    );
+
+    let restake = Transaction {
+        outs: vec![charms(&[(&token_app, staked(100, 510))])],
+        ..unstake_tx(&staking_nft(0), &staking_nft(0), 100)
+    };
+    assert_eq!(
+        validate(&token_app, &timelocked(restake, 510), &unstake_at(510), &w),
+        Err(ContractError::UndeclaredStake)
+    );
+    assert_eq!(
+        validate(
+            &nft_app,
+            &unstake_tx(&staking_nft(0), &staking_nft(10), 110),
+            &declare(ContractInput::Stake { height: 510 }),
+            &w
+        ),
+        Err(ContractError::UnsupportedOperation)
+    );
+}
+
+/// Tests that a reward passes the cap, emission and mint authority checks of
+/// any other mint, at a proven height.
+#[test]
+fn test_validate_unstake_reward_is_a_mint() {
+    let token_app = app(TOKEN);
+    let w = Data::empty();
+    let unstake = |change: &dyn Fn(NftContent) -> NftContent| {
+        let mut unstake = unstake_tx(&change(staking_nft(0)), &change(staking_nft(10)), 110);
+        unstake.coin_outs = Some(coin_outs(&[b"staker", b"reserve"]));
+        timelocked(unstake, 510)
+    };
+
+    let capped = unstake(&|content| NftContent {
+        max_mint_per_tx: Some(5),
+        ..content
+    });
+    assert_eq!(
+        validate(&token_app, &capped, &unstake_at(510), &w),
+        Err(ContractError::MintCapExceeded { minted: 10, cap: 5 })
+    );
+    let scheduled = unstake(&|content| NftContent {
+        emission: Some(EmissionSchedule {
+            start_height: 600,
+            ..emission()
+        }),
+        ..content
+    });
+    assert_eq!(
+        validate(&token_app, &scheduled, &unstake_at(510), &w),
+        Err(ContractError::EmissionExceeded {
+            minted: 10,
+            allowed: 0
+        })
+    );
+    let airdropped = unstake(&|content| NftContent {
+        airdrop: Some(Airdrop::default()),
+        ..content
+    });
+    assert_eq!(
+        validate(&token_app, &airdropped, &unstake_at(510), &w),
+        Err(ContractError::AirdropOnly)
+    );
+
+    let key = authority_key(1);
+    let authorized = unstake(&|content| NftContent {
+        mint_authority: Some(authority(&key)),
+        ..content
+    });
+    assert_eq!(
+        validate(&token_app, &authorized, &unstake_at(510), &w),
+        Err(ContractError::MissingMintSignature)
+    );
+    let signed = mint_witness(sign_mint(&key, &authorized));
+    assert_eq!(validate(&token_app, &authorized, &unstake_at(510), &signed), Ok(()));
+
+    let unproven = Data::from(&PublicInput {
+        version: 1,
+        op: ContractInput::Unstake { height: u64::MAX },
+        height_proof: None,
+    });
+    assert_eq!(
+        validate(&token_app, &unstake(&|content| content), &unproven, &w),
+        Err(ContractError::MissingHeightProof)
+    );
+}